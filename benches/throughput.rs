@@ -0,0 +1,175 @@
+//! Throughput of the pure scraping and matching paths on generated fixtures
+//!
+//! Run with `cargo bench --features bench`, every line reads `bench <name> ... <n> items/s`.
+
+use std::{collections::HashSet, hint::black_box, time::Instant};
+
+use unilist::{bench, normalize_title, Candidate, Similarity};
+
+const ROWS: usize = 10_000;
+const CANDIDATES: usize = 25;
+
+fn main() {
+    let rows = yt_rows();
+    measure("yt_row_parsing", rows.len(), || {
+        for row in &rows {
+            black_box(bench::parse_yt_row(row, None));
+        }
+    });
+
+    let apple = apple_rows();
+    measure("apple_row_parsing", apple.len(), || {
+        for (name, artist, album) in &apple {
+            black_box(bench::parse_apple_row(
+                name,
+                Some(artist),
+                None,
+                Some(album),
+                None,
+            ));
+        }
+    });
+
+    let spotify = spotify_rows();
+    measure("spotify_row_parsing", spotify.len(), || {
+        for (name, artists, index) in &spotify {
+            black_box(bench::parse_spotify_row(name, artists.clone(), Some(index)));
+        }
+    });
+
+    let recordings = apple_recordings();
+    measure("apple_json_ld_parsing", recordings.len(), || {
+        for recording in &recordings {
+            let recording = serde_json::from_str(recording).expect("Recordings are valid JSON");
+            black_box(bench::parse_apple_recording(&recording).ok());
+        }
+    });
+
+    let tracks = rows
+        .iter()
+        .filter_map(|row| bench::parse_yt_row(row, None))
+        .collect::<Vec<_>>();
+    measure("title_normalization", tracks.len(), || {
+        for track in &tracks {
+            black_box(normalize_title(&track.name));
+        }
+    });
+
+    measure("key_dedupe", tracks.len(), || {
+        let mut seen = HashSet::new();
+        for track in &tracks {
+            black_box(seen.insert(track.key()));
+        }
+    });
+
+    let candidates = candidates();
+    let searched = &tracks[..1000];
+    measure("candidate_ranking", searched.len(), || {
+        for track in searched {
+            black_box(bench::rank(
+                candidates.clone(),
+                track,
+                Similarity::default(),
+            ));
+        }
+    });
+}
+
+/// Runs `f` once to warm up, then reports the best of five runs
+fn measure(name: &str, items: usize, mut f: impl FnMut()) {
+    f();
+    let best = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .expect("Runs were made");
+    let rate = items as f64 / best.as_secs_f64();
+    println!("bench {name:<24} {best:>12.2?} {rate:>14.0} items/s");
+}
+
+/// Rows shaped like YouTube Music playlist rows, every tenth one repeated
+fn yt_rows() -> Vec<Vec<String>> {
+    (0..ROWS)
+        .map(|i| {
+            let n = if i % 10 == 9 { i - 1 } else { i };
+            vec![
+                format!("Song number {n} (Remastered {})", 1990 + n % 30),
+                format!("Artist {} feat. Guest {n}", n % 300),
+                format!("Album {}", n % 900),
+                "3:45".to_string(),
+                String::new(),
+            ]
+        })
+        .collect()
+}
+
+/// Name, artist line and album of Apple Music playlist rows
+fn apple_rows() -> Vec<(String, String, String)> {
+    (0..ROWS)
+        .map(|i| {
+            (
+                format!(" Song number {i} (Live) "),
+                format!("Artist {} & Guest {i}", i % 300),
+                format!("Album {}", i % 900),
+            )
+        })
+        .collect()
+}
+
+/// Title, artist links and index of Spotify playlist rows, every third one a collaboration
+fn spotify_rows() -> Vec<(String, Vec<String>, String)> {
+    (0..ROWS)
+        .map(|i| {
+            let mut artists = vec![format!("Artist {}", i % 300)];
+            if i % 3 == 0 {
+                artists.push(format!("Guest {i}"));
+            }
+            (format!("Song number {i}"), artists, (i + 1).to_string())
+        })
+        .collect()
+}
+
+/// `MusicRecording` JSON-LD of Apple Music song pages, a lone artist as an object and
+/// collaborations as a list
+fn apple_recordings() -> Vec<String> {
+    (0..ROWS)
+        .map(|i| {
+            let artist = |name: String| serde_json::json!({ "@type": "MusicGroup", "name": name });
+            let by_artist = if i % 3 == 0 {
+                serde_json::json!([
+                    artist(format!("Artist {}", i % 300)),
+                    artist(format!("Guest {i}"))
+                ])
+            } else {
+                artist(format!("Artist {}", i % 300))
+            };
+            serde_json::json!({
+                "@context": "http://schema.org",
+                "@type": "MusicRecording",
+                "name": format!("Song number {i}"),
+                "byArtist": by_artist,
+                "duration": "PT3M45S",
+                "inAlbum": { "@type": "MusicAlbum", "name": format!("Album {}", i % 900) },
+            })
+            .to_string()
+        })
+        .collect()
+}
+
+fn candidates() -> Vec<Candidate> {
+    (0..CANDIDATES)
+        .map(|i| Candidate {
+            title: format!("Song number {i} - Radio Edit"),
+            artist: format!("Artist {i}"),
+            link: format!("https://music.apple.com/us/song/{i}"),
+            track_number: None,
+            explicit: i % 2 == 0,
+            year: None,
+            album: None,
+            duration_secs: None,
+        })
+        .collect()
+}
@@ -0,0 +1,524 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+
+use headless_chrome::{protocol::cdp::Page, util::Timeout, Browser, Tab};
+
+use anyhow::anyhow;
+use url::Url;
+
+use crate::{converter::Options, is_short_link, Platform};
+
+/// Hides the usual headless tells, runs before any script of the page
+const STEALTH_SCRIPT: &str = r#"
+Object.defineProperty(navigator, 'webdriver', { get: () => undefined });
+Object.defineProperty(navigator, 'languages', { get: () => ['en-US', 'en'] });
+Object.defineProperty(navigator, 'plugins', { get: () => [1, 2, 3, 4, 5] });
+window.chrome = window.chrome || { runtime: {} };
+"#;
+
+/// How long the page must load no new resources to count as idle
+const NETWORK_IDLE_WINDOW: Duration = Duration::from_millis(500);
+
+/// How often the tab's URL is checked while a share link redirects
+const REDIRECT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a reused tab may take to evaluate a trivial script before it counts as hung
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Titles of the page Chrome shows in place of a site whose renderer crashed
+const CRASH_TITLES: &[&str] = &["Aw, Snap!", "He's Dead, Jim!"];
+
+/// No Chrome or Chromium binary was found and fetching one wasn't enabled
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserNotFound {
+    /// Why the system lookup failed
+    pub reason: String,
+}
+
+impl fmt::Display for BrowserNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Chrome/Chromium not found: {}", self.reason)
+    }
+}
+
+impl std::error::Error for BrowserNotFound {}
+
+/// What to wait for after navigating, before a scraper looks for its first elements
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum WaitStrategy {
+    /// Wait for the page's load event
+    #[default]
+    Navigated,
+    /// Wait until the page stops loading resources, most reliable for hydrating apps
+    Networkidle,
+    /// Don't wait, the scraper's selectors wait for their elements, fastest
+    SelectorOnly,
+}
+
+/// Page that didn't load after its retries, or loaded Chrome's crash page
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PageLoadFailed {
+    pub url: String,
+}
+
+impl fmt::Display for PageLoadFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to open {}", self.url)
+    }
+}
+
+impl std::error::Error for PageLoadFailed {}
+
+/// Whether an error says something about the tab rather than the page it shows
+///
+/// Failed loads, waits that timed out and crashes count, a search without results doesn't: a
+/// few ordinary misses in a row shouldn't replace a healthy tab.
+fn is_tab_failure(error: &anyhow::Error) -> bool {
+    error.is::<PageLoadFailed>() || error.chain().any(|cause| cause.is::<Timeout>())
+}
+
+/// Loads of a page tried before its navigation error is returned
+const NAVIGATE_ATTEMPTS: usize = 2;
+
+/// Opens a tab for scraping `platform` and navigates it to `url`, screenshotting a failed load
+pub(crate) fn open_page(
+    browser: &Browser,
+    url: &str,
+    platform: Platform,
+    options: &Options,
+) -> anyhow::Result<Arc<Tab>> {
+    let tab = open_warm_tab(browser, platform, options)?;
+    screenshot_on_error(&tab, options, navigate(&tab, url, options))?;
+    Ok(tab)
+}
+
+/// Navigates the tab to `url` and waits according to the configured strategy
+pub(crate) fn navigate(tab: &Tab, url: &str, options: &Options) -> anyhow::Result<()> {
+    navigate_with(tab, url, options.wait, options)
+}
+
+/// Navigates the tab to `url` and waits according to `wait`, retrying a failed load once
+///
+/// Errors name the page as `Failed to open {url}`.
+pub(crate) fn navigate_with(
+    tab: &Tab,
+    url: &str,
+    wait: WaitStrategy,
+    options: &Options,
+) -> anyhow::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match load(tab, url, wait, options) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < NAVIGATE_ATTEMPTS => {
+                log::warn!("Retrying {url} after {e:#}");
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e.context(PageLoadFailed {
+                    url: url.to_string(),
+                }))
+            }
+        }
+    }
+}
+
+fn load(tab: &Tab, url: &str, wait: WaitStrategy, options: &Options) -> anyhow::Result<()> {
+    tab.navigate_to(url)?;
+    match wait {
+        WaitStrategy::Navigated => {
+            tab.wait_until_navigated()?;
+        }
+        WaitStrategy::Networkidle => {
+            tab.wait_until_navigated()?;
+            wait_for_network_idle(tab, options.timeout)?;
+        }
+        WaitStrategy::SelectorOnly => {}
+    }
+    // Every selector would time out on the crash page, the retry loads the site again
+    if is_crashed(tab) {
+        log::warn!("Tab crashed loading {url}");
+        return Err(anyhow!("Tab crashed (Aw, Snap!)"));
+    }
+    Ok(())
+}
+
+/// Whether the tab shows Chrome's "Aw, Snap!" page, a crashed renderer fails scripts instead
+fn is_crashed(tab: &Tab) -> bool {
+    match tab.evaluate("document.title", false) {
+        Ok(title) => title
+            .value
+            .as_ref()
+            .and_then(|title| title.as_str())
+            .is_some_and(|title| CRASH_TITLES.iter().any(|crash| title.contains(crash))),
+        Err(e) => e.to_string().to_lowercase().contains("crash"),
+    }
+}
+
+fn wait_for_network_idle(tab: &Tab, timeout: Duration) -> anyhow::Result<()> {
+    let deadline = Instant::now() + timeout;
+    let mut resources = None;
+    loop {
+        let count = tab
+            .evaluate("performance.getEntriesByType('resource').length", false)?
+            .value
+            .and_then(|value| value.as_u64());
+        if count.is_some() && count == resources {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            log::warn!("Page didn't become idle within {timeout:?}");
+            return Ok(());
+        }
+        resources = count;
+        thread::sleep(NETWORK_IDLE_WINDOW);
+    }
+}
+
+/// Opens a tab with the configured timeout and, if enabled, the stealth overrides
+pub(crate) fn open_tab(browser: &Browser, options: &Options) -> anyhow::Result<Arc<Tab>> {
+    let tab = browser.new_tab()?;
+    tab.set_default_timeout(options.timeout);
+
+    if options.stealth {
+        tab.call_method(Page::AddScriptToEvaluateOnNewDocument {
+            source: STEALTH_SCRIPT.to_string(),
+            world_name: None,
+            include_command_line_api: None,
+        })?;
+    }
+
+    Ok(tab)
+}
+
+/// Accept buttons of the cookie consent banners, Spotify's OneTrust and Google's consent page
+const CONSENT_ACCEPT: &str = r#"#onetrust-accept-btn-handler, button[aria-label^="Accept all"], button[data-testid="accept-cookies"]"#;
+
+/// Opens a tab for scraping `platform`, first settling its consent and region pages if
+/// [`Options::warmup`] is set
+pub(crate) fn open_warm_tab(
+    browser: &Browser,
+    platform: Platform,
+    options: &Options,
+) -> anyhow::Result<Arc<Tab>> {
+    let tab = open_tab(browser, options)?;
+    if options.warmup {
+        // A wall on the homepage only costs the warm-up, the real navigation still runs
+        if let Err(e) = warm_up(&tab, platform, options) {
+            log::warn!("Warm-up of {platform:?} failed: {e:#}");
+        }
+    }
+    Ok(tab)
+}
+
+/// Visits the platform's homepage and accepts its cookies so the next navigation carries them
+fn warm_up(tab: &Tab, platform: Platform, options: &Options) -> anyhow::Result<()> {
+    let homepage = match platform {
+        Platform::Spotify => "https://open.spotify.com/".to_string(),
+        Platform::Apple => format!("https://music.apple.com/{}/browse", options.region),
+        Platform::Youtube => "https://music.youtube.com/".to_string(),
+        Platform::Unknown => return Ok(()),
+    };
+    navigate(tab, &homepage, options)?;
+    if dismiss_overlay(tab, CONSENT_ACCEPT) {
+        log::info!("Accepted the cookie consent of {platform:?}");
+        tab.wait_until_navigated()?;
+    }
+    Ok(())
+}
+
+/// Follows a share link's redirects, waiting until the tab lands on a full platform link
+pub(crate) fn resolve_short_link(
+    browser: &Browser,
+    url: &Url,
+    options: &Options,
+) -> anyhow::Result<Url> {
+    let tab = open_tab(browser, options)?;
+    // Closed whether or not the link resolved, failed resolves would leak a tab each
+    let resolved = follow_redirects(&tab, url, options);
+    if let Err(e) = tab.close(true) {
+        log::error!("Failed to close tab with {e:?}")
+    }
+    resolved
+}
+
+fn follow_redirects(tab: &Tab, url: &Url, options: &Options) -> anyhow::Result<Url> {
+    navigate(tab, url.as_str(), options)?;
+
+    // Some share pages redirect from a script after loading
+    let deadline = Instant::now() + options.timeout;
+    loop {
+        let current = Url::parse(&tab.get_url())?;
+        if !is_short_link(&current) && Platform::from_url(&current) != Platform::Unknown {
+            return Ok(current);
+        }
+        if Instant::now() >= deadline {
+            return Err(anyhow!(
+                "{url} didn't redirect to a known platform, stopped at {current}"
+            ));
+        }
+        thread::sleep(REDIRECT_POLL_INTERVAL);
+    }
+}
+
+/// Saves a screenshot of the tab to the configured directory when `result` is an error
+pub(crate) fn screenshot_on_error<T>(
+    tab: &Tab,
+    options: &Options,
+    result: anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    if result.is_err() {
+        screenshot_failure(tab, options);
+    }
+    result
+}
+
+/// Saves a screenshot of the failed page if a screenshot directory is configured
+pub(crate) fn screenshot_failure(tab: &Tab, options: &Options) {
+    let Some(dir) = &options.screenshot_dir else {
+        return;
+    };
+    match save_screenshot(tab, dir) {
+        Ok(path) => log::warn!(
+            "Saved a screenshot of the failed page to {}",
+            path.display()
+        ),
+        Err(e) => log::error!("Failed to capture a screenshot: {e:?}"),
+    }
+}
+
+/// Writes the tab as `{unix millis}-{platform}.png` into `dir`
+fn save_screenshot(tab: &Tab, dir: &Path) -> anyhow::Result<PathBuf> {
+    let platform = Url::parse(&tab.get_url())
+        .map(|url| Platform::from_url(&url))
+        .unwrap_or(Platform::Unknown);
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let png = tab.capture_screenshot(Page::CaptureScreenshotFormatOption::Png, None, None, true)?;
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{timestamp}-{platform:?}.png").to_lowercase());
+    fs::write(&path, png)?;
+    Ok(path)
+}
+
+/// Clicks the dismiss button of an overlay if the page shows one, returning whether it did
+pub(crate) fn dismiss_overlay(tab: &Tab, dismiss_selector: &str) -> bool {
+    let Ok(button) = tab.find_element(dismiss_selector) else {
+        return false;
+    };
+    match button.click() {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("Failed to dismiss overlay: {e:?}");
+            false
+        }
+    }
+}
+
+/// Counting semaphore capping the tabs workers keep open at once, whatever the concurrency
+#[derive(Debug)]
+pub(crate) struct TabSlots {
+    capacity: usize,
+    free: Mutex<usize>,
+    released: Condvar,
+}
+
+impl TabSlots {
+    pub fn new(max_open_tabs: usize) -> Self {
+        Self {
+            capacity: max_open_tabs.max(1),
+            free: Mutex::new(max_open_tabs.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Tabs that may be open at once
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Blocks until a tab may be opened
+    fn acquire(&self) {
+        let mut free = self.free.lock().unwrap_or_else(|e| e.into_inner());
+        if *free == 0 {
+            log::info!("All tab slots are taken, waiting for one to free up");
+        }
+        while *free == 0 {
+            free = self.released.wait(free).unwrap_or_else(|e| e.into_inner());
+        }
+        *free -= 1;
+    }
+
+    fn release(&self) {
+        *self.free.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.released.notify_one();
+    }
+}
+
+/// Tabs handed out to whichever worker asks next, at most `capacity` of them ever made
+///
+/// Pooled tabs keep their [`TabSlots`] slot, so workers beyond the capacity wait for a tab to
+/// come back rather than for a slot no pooled tab gives up.
+pub(crate) struct TabPool<T> {
+    capacity: usize,
+    /// Tabs waiting for a worker and the count of tabs made so far
+    state: Mutex<(Vec<T>, usize)>,
+    returned: Condvar,
+}
+
+impl<T> TabPool<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new((vec![], 0)),
+            returned: Condvar::new(),
+        }
+    }
+
+    /// Runs `f` with a free tab, made with `make` while under the capacity and waited for after
+    pub fn with<R>(&self, make: impl FnOnce() -> T, f: impl FnOnce(&mut T) -> R) -> R {
+        let free = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            loop {
+                if let Some(tab) = state.0.pop() {
+                    break Some(tab);
+                }
+                if state.1 < self.capacity {
+                    state.1 += 1;
+                    break None;
+                }
+                state = self.returned.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+        };
+        let mut tab = free.unwrap_or_else(make);
+        let result = f(&mut tab);
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .0
+            .push(tab);
+        self.returned.notify_one();
+        result
+    }
+}
+
+/// Tab reused by one conversion worker, replaced when it hangs or keeps failing
+pub(crate) struct WorkerTab<'a> {
+    browser: &'a Browser,
+    options: &'a Options,
+    tab: Option<Arc<Tab>>,
+    /// Consecutive failed searches of the current tab
+    failures: usize,
+}
+
+impl<'a> WorkerTab<'a> {
+    pub fn new(browser: &'a Browser, options: &'a Options) -> Self {
+        Self {
+            browser,
+            options,
+            tab: None,
+            failures: 0,
+        }
+    }
+
+    /// Returns a responsive tab, opening a fresh one if the current one doesn't respond
+    pub fn get(&mut self) -> anyhow::Result<Arc<Tab>> {
+        if let Some(tab) = &self.tab {
+            if !is_responsive(tab, self.options.timeout) {
+                log::warn!("Tab stopped responding, replacing it");
+            } else if is_crashed(tab) {
+                log::warn!("Tab crashed, replacing it");
+            } else {
+                return Ok(tab.clone());
+            }
+            self.recycle();
+        }
+        self.options.tab_slots.acquire();
+        let tab = open_tab(self.browser, self.options).inspect_err(|_| {
+            self.options.tab_slots.release();
+        })?;
+        self.tab = Some(tab.clone());
+        Ok(tab)
+    }
+
+    /// Records the outcome of a search, replacing the tab after too many tab failures in a row
+    pub fn record<T>(&mut self, result: &anyhow::Result<T>) {
+        match result {
+            Err(e) if is_tab_failure(e) => {}
+            _ => {
+                self.failures = 0;
+                return;
+            }
+        }
+        self.failures += 1;
+        if self.failures >= self.options.tab_failure_threshold {
+            log::warn!(
+                "Tab failed {} searches in a row, replacing it",
+                self.failures
+            );
+            self.recycle();
+        }
+    }
+
+    /// Closes the current tab, the next [`WorkerTab::get`] opens a fresh one
+    pub fn recycle(&mut self) {
+        self.failures = 0;
+        if let Some(tab) = self.tab.take() {
+            if let Err(e) = tab.close(true) {
+                log::error!("Failed to close tab with {e:?}")
+            }
+            self.options.tab_slots.release();
+        }
+    }
+}
+
+impl Drop for WorkerTab<'_> {
+    fn drop(&mut self) {
+        self.recycle();
+    }
+}
+
+/// Whether the tab still evaluates scripts, a hung tab would stall every later search
+fn is_responsive(tab: &Tab, timeout: Duration) -> bool {
+    tab.set_default_timeout(HEALTH_CHECK_TIMEOUT);
+    let responsive = tab.evaluate("1", false).is_ok();
+    tab.set_default_timeout(timeout);
+    responsive
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn workers_beyond_the_pool_capacity_wait_for_a_returned_tab() {
+        let pool = TabPool::new(2);
+        let made = AtomicUsize::new(0);
+        let searches = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..9 {
+                scope.spawn(|| {
+                    for _ in 0..5 {
+                        pool.with(
+                            || made.fetch_add(1, Ordering::Relaxed),
+                            |_| {
+                                thread::sleep(Duration::from_millis(1));
+                                searches.fetch_add(1, Ordering::Relaxed);
+                            },
+                        );
+                    }
+                });
+            }
+        });
+        assert_eq!(made.load(Ordering::Relaxed), 2);
+        assert_eq!(searches.load(Ordering::Relaxed), 45);
+    }
+}
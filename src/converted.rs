@@ -0,0 +1,294 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Platform, Track, TrackKey};
+
+/// Link found for a track, as kept in a file of already converted tracks
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ConvertedTrack {
+    pub name: String,
+    pub artist: String,
+    pub link: String,
+    /// Recording code of the track, files written before it was kept have none
+    #[serde(default)]
+    pub isrc: Option<String>,
+    /// Unix time the link was found, files written before it was kept have none
+    #[serde(default)]
+    pub converted_at: Option<u64>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Recording code without the dashes and casing some exports add, e.g. `us-rc1-76-07839`
+fn normalize_isrc(isrc: &str) -> String {
+    isrc.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Links of each source track by target platform, keyed `Artist - Title`, with the track's
+/// recording code when it is known
+///
+/// Written by `--save-mapping` and readable as `--existing`, so runs converting overlapping
+/// playlists can share their decisions:
+///
+/// ```json
+/// { "Band - Song": { "isrc": "USRC17607839", "apple": "https://music.apple.com/...", "spotify": "https://open.spotify.com/..." } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Mapping(BTreeMap<String, MappingEntry>);
+
+/// Links of one track in a [`Mapping`], files written before the code was kept have none
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+struct MappingEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    isrc: Option<String>,
+    #[serde(flatten)]
+    links: BTreeMap<Platform, String>,
+}
+
+impl Mapping {
+    /// Reads a mapping file, a missing file maps nothing yet
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Stores the link of a track on `platform`, keeping its links on the others
+    pub fn insert(&mut self, track: &Track, platform: Platform, link: impl Into<String>) {
+        let key = format!("{} - {}", track.artist, track.name);
+        let entry = self.0.entry(key).or_default();
+        if track.isrc.is_some() {
+            entry.isrc.clone_from(&track.isrc);
+        }
+        entry.links.insert(platform, link.into());
+    }
+
+    /// Tracks with a link on `platform`, the key split back at its first ` - `
+    fn tracks(&self, platform: Platform) -> impl Iterator<Item = (Track, &str)> {
+        self.0.iter().filter_map(move |(key, entry)| {
+            let (artist, name) = key.split_once(" - ")?;
+            let track = Track {
+                name: name.to_string(),
+                artist: artist.to_string(),
+                isrc: entry.isrc.clone(),
+                ..Default::default()
+            };
+            Some((track, entry.links.get(&platform)?.as_str()))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Either form of a file of earlier links
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    Tracks(Vec<ConvertedTrack>),
+    Mapping(Mapping),
+}
+
+/// Links found in earlier runs for one target platform, looked up by [`Track::isrc`] first and
+/// [`Track::key`] for tracks without one
+///
+/// The recording code finds the same song across source playlists that spell it differently.
+#[derive(Debug, Default)]
+pub struct ConvertedLinks {
+    links: HashMap<TrackKey, ConvertedTrack>,
+    /// Key of the link stored for each recording code
+    isrcs: HashMap<String, TrackKey>,
+    /// Mapping file the links were read from, saved back with the other platforms' links kept
+    mapping: Option<(Mapping, Platform)>,
+}
+
+impl ConvertedLinks {
+    /// Reads a JSON array of [`ConvertedTrack`]s or the `target` links of a [`Mapping`], a
+    /// missing file holds no links yet
+    ///
+    /// Mapping links carry no time they were found, so `--cache-ttl` searches them again.
+    pub fn load(path: impl AsRef<Path>, target: Platform) -> anyhow::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let converted = match serde_json::from_str(&content)? {
+            Stored::Tracks(converted) => converted,
+            Stored::Mapping(mapping) => {
+                let mut links = Self::default();
+                for (track, link) in mapping.tracks(target) {
+                    let key = track.key();
+                    if let Some(isrc) = &track.isrc {
+                        links.isrcs.insert(normalize_isrc(isrc), key.clone());
+                    }
+                    links.links.insert(
+                        key,
+                        ConvertedTrack {
+                            name: track.name,
+                            artist: track.artist,
+                            link: link.to_string(),
+                            isrc: track.isrc,
+                            converted_at: None,
+                        },
+                    );
+                }
+                links.mapping = Some((mapping, target));
+                return Ok(links);
+            }
+        };
+        let mut links = Self::default();
+        for converted in converted {
+            let key = Track {
+                name: converted.name.clone(),
+                artist: converted.artist.clone(),
+                ..Default::default()
+            }
+            .key();
+            if let Some(isrc) = &converted.isrc {
+                links.isrcs.insert(normalize_isrc(isrc), key.clone());
+            }
+            links.links.insert(key, converted);
+        }
+        Ok(links)
+    }
+
+    pub fn get(&self, track: &Track) -> Option<&str> {
+        let by_isrc = track
+            .isrc
+            .as_ref()
+            .and_then(|isrc| self.isrcs.get(&normalize_isrc(isrc)))
+            .and_then(|key| self.links.get(key));
+        by_isrc
+            .or_else(|| self.links.get(&track.key()))
+            .map(|converted| converted.link.as_str())
+    }
+
+    /// Stores the link of a track, a link already stored keeps the time it was found
+    pub fn insert(&mut self, track: &Track, link: impl Into<String>) {
+        let link = link.into();
+        let converted_at = match self.links.get(&track.key()) {
+            Some(stored) if stored.link == link => stored.converted_at,
+            _ => Some(unix_now()),
+        };
+        let converted = ConvertedTrack {
+            name: track.name.clone(),
+            artist: track.artist.clone(),
+            link,
+            isrc: track.isrc.clone(),
+            converted_at,
+        };
+        if let Some(isrc) = &track.isrc {
+            self.isrcs.insert(normalize_isrc(isrc), track.key());
+        }
+        self.links.insert(track.key(), converted);
+    }
+
+    /// Drops the links found longer than `ttl` ago so they are searched again, returning how
+    /// many expired
+    ///
+    /// Links of files written before the time was kept have no age and expire too.
+    pub fn expire(&mut self, ttl: Duration) -> usize {
+        let oldest = unix_now().saturating_sub(ttl.as_secs());
+        let before = self.links.len();
+        self.links
+            .retain(|_, converted| converted.converted_at.is_some_and(|at| at >= oldest));
+        let links = &self.links;
+        self.isrcs.retain(|_, key| links.contains_key(key));
+        before - self.links.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Writes the links sorted by track so reruns produce stable diffs, in the form they were
+    /// read in
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        if let Some((mapping, target)) = &self.mapping {
+            let mut mapping = mapping.clone();
+            for converted in self.links.values() {
+                let track = Track {
+                    name: converted.name.clone(),
+                    artist: converted.artist.clone(),
+                    isrc: converted.isrc.clone(),
+                    ..Default::default()
+                };
+                mapping.insert(&track, *target, &converted.link);
+            }
+            return mapping.save(path);
+        }
+        let sorted = self.links.iter().collect::<BTreeMap<_, _>>();
+        let converted = sorted.into_values().collect::<Vec<_>>();
+        fs::write(path, serde_json::to_string_pretty(&converted)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping_links_are_found_by_isrc_after_a_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("unilist-mapping-{}.json", std::process::id()));
+        let saved = Track {
+            name: "Song".to_string(),
+            artist: "Band".to_string(),
+            isrc: Some("USRC17607839".to_string()),
+            ..Default::default()
+        };
+        let mut mapping = Mapping::default();
+        mapping.insert(
+            &saved,
+            Platform::Spotify,
+            "https://open.spotify.com/track/1",
+        );
+        mapping.save(&path).unwrap();
+
+        let links = ConvertedLinks::load(&path, Platform::Spotify).unwrap();
+        fs::remove_file(&path).unwrap();
+        let respelled = Track {
+            name: "Song (Remastered)".to_string(),
+            artist: "The Band".to_string(),
+            isrc: Some("us-rc1-76-07839".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            links.get(&respelled),
+            Some("https://open.spotify.com/track/1")
+        );
+    }
+}
@@ -0,0 +1,1318 @@
+use std::{
+    ffi::OsStr,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use headless_chrome::{browser::default_executable, Browser, LaunchOptions, Tab};
+use url::Url;
+
+use crate::{
+    browser::{
+        navigate_with, open_tab, resolve_short_link, screenshot_on_error, BrowserNotFound, TabPool,
+        TabSlots, WaitStrategy, WorkerTab,
+    },
+    http::ApiClient,
+    is_ambiguous_title, is_short_link, link_id,
+    progress::{Progress, Reporter},
+    resolve::{self, Search, SearchQuery, TrackResolver, RELAXATIONS},
+    same_provider_link, scrape,
+    selectors::LAYOUT_PROBES,
+    youtube_video_id, AlbumMode, AmbiguousPolicy, ArtistSelection, Candidate, ConsideredCandidate,
+    Event, LayoutChanged, Match, Matcher, Platform, Playlist, Score, Selectors, Similarity, Track,
+    UnsupportedPlatform, VersionPreference, DEFAULT_ARTIST_BLOCKLIST,
+};
+
+/// How long the browser connection may stay silent, long conversions wait on pages a lot
+const IDLE_BROWSER_TIMEOUT: Duration = Duration::from_secs(1000000);
+
+/// Tabs open at once unless [`ConverterBuilder::max_open_tabs`] says otherwise
+const DEFAULT_MAX_OPEN_TABS: usize = 8;
+
+/// Messages of removed or unavailable songs, matched case-insensitively against the page
+const DEAD_PAGE_MESSAGES: &[&str] = &[
+    "page not found",
+    "couldn't find",
+    "can't be found",
+    "video unavailable",
+    "this video isn't available",
+    "this content is not available",
+];
+
+/// Settings shared by the scrapers and resolvers
+#[derive(Debug, Clone)]
+pub(crate) struct Options {
+    /// Default timeout for waiting on page elements
+    pub timeout: Duration,
+    /// Number of tabs resolving tracks in parallel
+    pub concurrency: usize,
+    /// Tabs the workers may keep open at once, shared by every copy of the options
+    pub tab_slots: Arc<TabSlots>,
+    /// Storefront used in target search URLs, e.g. `us`
+    pub region: String,
+    pub matcher: Matcher,
+    /// Fuzzy metric of the scores
+    pub similarity: Similarity,
+    /// Artists of a track's line that are searched and scored
+    pub artist_selection: ArtistSelection,
+    /// How tracks with a title like `Go` are matched
+    pub ambiguous_policy: AmbiguousPolicy,
+    /// How the track's album weighs on its match
+    pub album_mode: AlbumMode,
+    /// Lowest confidence the scored matcher accepts
+    pub min_confidence: f64,
+    /// Version picked among equally good candidates
+    pub prefer: VersionPreference,
+    /// Lowest title similarity the scored matcher accepts
+    pub title_threshold: f64,
+    /// Lowest artist similarity the scored matcher accepts
+    pub artist_threshold: f64,
+    /// Search the title alone when title and artist find nothing
+    pub title_only_fallback: bool,
+    /// Search name and artist exchanged when every other search missed
+    pub swap_fallback: bool,
+    /// Only accept songs released in the track's year, when it's known
+    pub match_year: bool,
+    /// Artist substrings disqualifying a candidate, see [`DEFAULT_ARTIST_BLOCKLIST`]
+    pub artist_blocklist: Vec<String>,
+    /// Candidates the scored matcher weighed, shared by every copy of the options, `None` unless
+    /// recorded
+    pub considered: Option<Arc<Mutex<Vec<ConsideredCandidate>>>>,
+    /// Tracks after which scrapers stop, unlimited if `None`
+    pub max_tracks: Option<usize>,
+    /// Consecutive failed searches before a worker replaces its tab
+    pub tab_failure_threshold: usize,
+    /// Longest a single track's search may take, unlimited if `None`
+    pub track_timeout: Option<Duration>,
+    /// Consecutive scrolls without new tracks before the Spotify scrape stops
+    pub scroll_stall_threshold: usize,
+    /// Pause between two Spotify scrolls, lengthened by up to half of it at random
+    pub scroll_delay: Duration,
+    /// Scrolls after which the Spotify scrape stops even if rows keep coming, unlimited if `None`
+    pub max_scroll_iterations: Option<usize>,
+    /// File keeping the tracks a Spotify scrape collected so far, resumed from on a rerun
+    pub scroll_checkpoint: Option<PathBuf>,
+    /// Hide `navigator.webdriver` and similar headless tells from the pages
+    pub stealth: bool,
+    /// Print every newly scraped track to stderr
+    pub stream_tracks: bool,
+    /// Keep songs a playlist repeats, only dropping rows read twice while scrolling
+    pub keep_duplicates: bool,
+    /// Write scrape and conversion [`Event`]s to stdout as they happen
+    pub event_stream: bool,
+    /// Give album rows without an artist the one under the album title
+    pub artist_from_album_header: bool,
+    /// Keep the album art URL of every scraped row
+    pub cover_art: bool,
+    /// Reject URLs of unknown hosts instead of following them as share links
+    pub strict_platform: bool,
+    /// Visit the platform's homepage and accept its cookies before a scrape navigates
+    pub warmup: bool,
+    /// What scrapers wait for after navigating
+    pub wait: WaitStrategy,
+    /// How scraping and conversion progress is reported
+    pub progress: Progress,
+    /// Directory receiving screenshots of pages that failed to scrape or search
+    pub screenshot_dir: Option<PathBuf>,
+    /// CSS selectors of the target resolvers
+    pub selectors: Selectors,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(20),
+            concurrency: 1,
+            tab_slots: Arc::new(TabSlots::new(DEFAULT_MAX_OPEN_TABS)),
+            region: "us".to_string(),
+            matcher: Matcher::default(),
+            similarity: Similarity::default(),
+            artist_selection: ArtistSelection::default(),
+            ambiguous_policy: AmbiguousPolicy::default(),
+            album_mode: AlbumMode::default(),
+            min_confidence: 0.8,
+            prefer: VersionPreference::default(),
+            title_threshold: 0.85,
+            artist_threshold: 0.7,
+            title_only_fallback: false,
+            swap_fallback: false,
+            match_year: false,
+            considered: None,
+            artist_blocklist: DEFAULT_ARTIST_BLOCKLIST
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect(),
+            max_tracks: None,
+            tab_failure_threshold: 3,
+            track_timeout: None,
+            scroll_stall_threshold: 3,
+            scroll_delay: Duration::from_millis(300),
+            max_scroll_iterations: None,
+            scroll_checkpoint: None,
+            stealth: false,
+            stream_tracks: false,
+            keep_duplicates: false,
+            event_stream: false,
+            artist_from_album_header: true,
+            cover_art: false,
+            strict_platform: false,
+            warmup: false,
+            wait: WaitStrategy::default(),
+            progress: Progress::default(),
+            screenshot_dir: None,
+            selectors: Selectors::default(),
+        }
+    }
+}
+
+/// Configures the browser and conversion options of a [`Converter`]
+#[derive(Debug, Clone)]
+pub struct ConverterBuilder {
+    headless: bool,
+    proxy: Option<String>,
+    chrome_path: Option<PathBuf>,
+    chrome_args: Vec<String>,
+    fetch_browser: bool,
+    user_data_dir: Option<PathBuf>,
+    api_rate_limit: f64,
+    options: Options,
+}
+
+impl Default for ConverterBuilder {
+    fn default() -> Self {
+        Self {
+            headless: true,
+            proxy: None,
+            chrome_path: None,
+            chrome_args: vec![],
+            fetch_browser: false,
+            user_data_dir: None,
+            api_rate_limit: 5.0,
+            options: Options::default(),
+        }
+    }
+}
+
+impl ConverterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run the browser without a window, enabled by default
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Timeout for waiting on page elements, 20 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// Proxy server for the browser, e.g. `socks5://127.0.0.1:1080`
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Browser profile directory kept between runs, so solved CAPTCHAs and logins carry over
+    ///
+    /// A fresh temporary profile is used by default.
+    pub fn user_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.user_data_dir = Some(dir.into());
+        self
+    }
+
+    /// Chrome or Chromium binary to launch instead of the one found on the system
+    pub fn chrome_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.chrome_path = Some(path.into());
+        self
+    }
+
+    /// Download a known-good Chromium when none is installed, disabled by default
+    pub fn fetch_browser(mut self, fetch_browser: bool) -> Self {
+        self.fetch_browser = fetch_browser;
+        self
+    }
+
+    /// Extra Chrome command line flag, e.g. `--no-sandbox` in containers
+    pub fn chrome_arg(mut self, arg: impl Into<String>) -> Self {
+        self.chrome_args.push(arg.into());
+        self
+    }
+
+    /// Requests per second sent to platform APIs, 5 by default
+    pub fn api_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.api_rate_limit = requests_per_second;
+        self
+    }
+
+    /// Number of tabs resolving tracks in parallel, 1 by default
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.options.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Tabs the workers may keep open at once, 8 by default
+    ///
+    /// Workers beyond the cap wait for a tab to close, so a high concurrency can't exhaust
+    /// Chrome's memory.
+    pub fn max_open_tabs(mut self, max_open_tabs: usize) -> Self {
+        self.options.tab_slots = Arc::new(TabSlots::new(max_open_tabs));
+        self
+    }
+
+    /// Consecutive failed searches before a worker replaces its tab, 3 by default
+    pub fn tab_failure_threshold(mut self, threshold: usize) -> Self {
+        self.options.tab_failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Longest a single track's search may take before it counts as unmatched, unlimited by
+    /// default
+    ///
+    /// Caps the whole search while [`ConverterBuilder::timeout`] applies to each element wait.
+    pub fn track_timeout(mut self, timeout: Duration) -> Self {
+        self.options.track_timeout = Some(timeout);
+        self
+    }
+
+    /// Storefront used when searching the target platform, `us` by default
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.options.region = region.into();
+        self
+    }
+
+    /// Strategy for picking a song among the search results
+    pub fn matcher(mut self, matcher: Matcher) -> Self {
+        self.options.matcher = matcher;
+        self
+    }
+
+    /// How tracks with a one-word title of a few letters like `Go` are matched, strictly by
+    /// default
+    pub fn ambiguous_policy(mut self, policy: AmbiguousPolicy) -> Self {
+        self.options.ambiguous_policy = policy;
+        self
+    }
+
+    /// How the track's album weighs on its match, a tiebreaker among similar candidates by
+    /// default
+    pub fn album_mode(mut self, mode: AlbumMode) -> Self {
+        self.options.album_mode = mode;
+        self
+    }
+
+    /// Artists of a track's line that are searched and scored, the whole line by default
+    pub fn artist_selection(mut self, selection: ArtistSelection) -> Self {
+        self.options.artist_selection = selection;
+        self
+    }
+
+    /// Fuzzy metric of the scores, Jaro-Winkler by default
+    pub fn similarity(mut self, similarity: Similarity) -> Self {
+        self.options.similarity = similarity;
+        self
+    }
+
+    /// Lowest confidence in `0..=1` accepted by [`Matcher::Scored`], 0.8 by default
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.options.min_confidence = min_confidence;
+        self
+    }
+
+    /// Explicit or clean version picked among tied candidates, either by default
+    pub fn prefer(mut self, prefer: VersionPreference) -> Self {
+        self.options.prefer = prefer;
+        self
+    }
+
+    /// Lowest title similarity in `0..=1` accepted by [`Matcher::Scored`], 0.85 by default
+    ///
+    /// Titles vary less across platforms than artist lines, which may list collaborators
+    /// differently, so the title threshold is the stricter one by default.
+    pub fn title_threshold(mut self, threshold: f64) -> Self {
+        self.options.title_threshold = threshold;
+        self
+    }
+
+    /// Lowest artist similarity in `0..=1` accepted by [`Matcher::Scored`], 0.7 by default
+    pub fn artist_threshold(mut self, threshold: f64) -> Self {
+        self.options.artist_threshold = threshold;
+        self
+    }
+
+    /// Consecutive scrolls without new tracks before the Spotify scrape stops, 3 by default
+    pub fn scroll_stall_threshold(mut self, threshold: usize) -> Self {
+        self.options.scroll_stall_threshold = threshold.max(1);
+        self
+    }
+
+    /// Pause between two Spotify scrolls so rows hydrate, 300ms plus up to half of it at random
+    /// by default
+    pub fn scroll_delay(mut self, delay: Duration) -> Self {
+        self.options.scroll_delay = delay;
+        self
+    }
+
+    /// Stop scraping once this many tracks are collected, unlimited by default
+    pub fn max_tracks(mut self, max_tracks: usize) -> Self {
+        self.options.max_tracks = Some(max_tracks);
+        self
+    }
+
+    /// Override `navigator.webdriver` and similar headless tells before pages load
+    pub fn stealth(mut self, stealth: bool) -> Self {
+        self.options.stealth = stealth;
+        self
+    }
+
+    /// Fill the artist of album rows that omit it from the album header, on by default
+    pub fn artist_from_album_header(mut self, enabled: bool) -> Self {
+        self.options.artist_from_album_header = enabled;
+        self
+    }
+
+    /// Fail on any URL whose host isn't a known platform, share links included, off by default
+    pub fn strict_platform(mut self, strict: bool) -> Self {
+        self.options.strict_platform = strict;
+        self
+    }
+
+    /// Settle the consent and region pages on the platform's homepage before scraping, off by
+    /// default
+    pub fn warmup(mut self, warmup: bool) -> Self {
+        self.options.warmup = warmup;
+        self
+    }
+
+    /// Keep the album art URL shown on each scraped row in [`Track::cover_url`]
+    pub fn cover_art(mut self, cover_art: bool) -> Self {
+        self.options.cover_art = cover_art;
+        self
+    }
+
+    /// Reject songs released in another year than the track, off by default
+    ///
+    /// Songs are always preferred when their year matches. With this set, a matched song whose
+    /// search result doesn't show its year is checked on its page, a navigation per track.
+    pub fn match_year(mut self, match_year: bool) -> Self {
+        self.options.match_year = match_year;
+        self
+    }
+
+    /// Artist or channel substrings disqualifying a candidate, ignoring case,
+    /// [`DEFAULT_ARTIST_BLOCKLIST`] unless set
+    ///
+    /// An entry found in the track's own artist is ignored for that track, so karaoke acts still
+    /// convert.
+    pub fn artist_blocklist(
+        mut self,
+        blocklist: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.options.artist_blocklist = blocklist.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Keep every candidate [`Matcher::Scored`] weighs for [`Converter::considered_candidates`],
+    /// off by default
+    pub fn record_candidates(mut self, enabled: bool) -> Self {
+        self.options.considered = enabled.then(Default::default);
+        self
+    }
+
+    /// Search the title alone when title and artist find nothing, off by default
+    ///
+    /// Only songs whose artist reaches the artist threshold qualify.
+    pub fn title_only_fallback(mut self, enabled: bool) -> Self {
+        self.options.title_only_fallback = enabled;
+        self
+    }
+
+    /// Write an [`Event`] line to stdout for every scraped, converted and unmatched track, off
+    /// by default
+    pub fn event_stream(mut self, enabled: bool) -> Self {
+        self.options.event_stream = enabled;
+        self
+    }
+
+    /// Keep songs the playlist itself repeats, off by default
+    ///
+    /// Scrolling re-reads rows, which Spotify scrapes drop by song. With this set they are told
+    /// apart by the row's index instead, so a song listed twice is kept twice.
+    pub fn keep_duplicates(mut self, enabled: bool) -> Self {
+        self.options.keep_duplicates = enabled;
+        self
+    }
+
+    /// Search name and artist exchanged when every other search missed, off by default
+    ///
+    /// Recovers rows a scraper read with their columns reversed. The swapped song must reach the
+    /// minimum confidence whatever the matcher.
+    pub fn swap_fallback(mut self, enabled: bool) -> Self {
+        self.options.swap_fallback = enabled;
+        self
+    }
+
+    /// Print each track to stderr as soon as the scraper finds it
+    pub fn stream_tracks(mut self, stream_tracks: bool) -> Self {
+        self.options.stream_tracks = stream_tracks;
+        self
+    }
+
+    /// What scrapers wait for after navigating, [`WaitStrategy::Navigated`] by default
+    pub fn wait(mut self, wait: WaitStrategy) -> Self {
+        self.options.wait = wait;
+        self
+    }
+
+    /// How scraping and conversion progress is reported on stderr, silent by default
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.options.progress = progress;
+        self
+    }
+
+    /// Stop a Spotify scrape after this many scrolls with a warning that it may be incomplete,
+    /// unlimited by default
+    ///
+    /// Guards against pages that never stop appending rows, unlike
+    /// [`ConverterBuilder::max_tracks`] which caps a playlist known to be huge.
+    pub fn max_scroll_iterations(mut self, iterations: usize) -> Self {
+        self.options.max_scroll_iterations = Some(iterations.max(1));
+        self
+    }
+
+    /// Keep the tracks a Spotify scrape collected in `path` as it scrolls, disabled by default
+    ///
+    /// A rerun starts from the saved tracks and only adds the rows it hasn't seen, so a playlist
+    /// too long for one session completes over several. The file is removed once a scrape
+    /// reaches the end.
+    pub fn scroll_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.scroll_checkpoint = Some(path.into());
+        self
+    }
+
+    /// Save a screenshot into `dir` whenever a scraper or resolver fails, disabled by default
+    pub fn screenshot_on_error(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.screenshot_dir = Some(dir.into());
+        self
+    }
+
+    /// CSS selectors of the target resolvers, the built-in ones by default
+    pub fn selectors(mut self, selectors: Selectors) -> Self {
+        self.options.selectors = selectors;
+        self
+    }
+
+    /// Launches the browser, failing with [`BrowserNotFound`] if there is none to launch
+    pub fn build(self) -> anyhow::Result<Converter> {
+        // Without a path the launcher downloads Chromium, which only happens when asked for
+        let chrome_path = match (&self.chrome_path, self.fetch_browser) {
+            (Some(path), _) => Some(path.clone()),
+            (None, true) => default_executable().ok(),
+            (None, false) => {
+                Some(default_executable().map_err(|reason| BrowserNotFound { reason })?)
+            }
+        };
+        let options = LaunchOptions::default_builder()
+            .headless(self.headless)
+            .idle_browser_timeout(IDLE_BROWSER_TIMEOUT)
+            .proxy_server(self.proxy.as_deref())
+            .path(chrome_path)
+            .user_data_dir(self.user_data_dir)
+            .args(self.chrome_args.iter().map(OsStr::new).collect())
+            .build()?;
+        let browser = Browser::new(options)?;
+
+        Ok(Converter {
+            browser,
+            http: Arc::new(ApiClient::new(self.api_rate_limit)),
+            options: self.options,
+            resolvers: vec![],
+        })
+    }
+}
+
+/// Scrapes playlists and finds their tracks on other platforms
+pub struct Converter {
+    browser: Browser,
+    http: Arc<ApiClient>,
+    options: Options,
+    resolvers: Vec<ResolverStep>,
+}
+
+/// Resolver of the chain [`Converter::convert`] tries
+enum ResolverStep {
+    /// The target platform's search page, see [`Converter::with_browser_search`]
+    Browser,
+    Added(Box<dyn TrackResolver>),
+}
+
+impl Converter {
+    pub fn builder() -> ConverterBuilder {
+        ConverterBuilder::new()
+    }
+
+    /// Candidates weighed so far if [`ConverterBuilder::record_candidates`] is set, in search order
+    pub fn considered_candidates(&self) -> Vec<ConsideredCandidate> {
+        self.options
+            .considered
+            .as_ref()
+            .map(|considered| {
+                considered
+                    .lock()
+                    .expect("Candidate log isn't poisoned")
+                    .clone()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Tries `resolver` on the tracks the resolvers added earlier didn't find
+    ///
+    /// Added resolvers replace the browser search, [`Converter::with_browser_search`] puts it
+    /// back where it should be tried.
+    pub fn with_resolver(mut self, resolver: impl TrackResolver + 'static) -> Self {
+        self.resolvers.push(ResolverStep::Added(Box::new(resolver)));
+        self
+    }
+
+    /// Searches the target platform's site in the browser for the tracks the resolvers added
+    /// earlier didn't find, the only resolver of a converter without added ones
+    pub fn with_browser_search(mut self) -> Self {
+        self.resolvers.push(ResolverStep::Browser);
+        self
+    }
+
+    /// Rate limited client for platform APIs, clone it to share its limit with a resolver
+    pub fn http(&self) -> &Arc<ApiClient> {
+        &self.http
+    }
+
+    /// Collects the tracks of the playlist behind `url`
+    ///
+    /// Share links like `https://spotify.link/...` are followed to the playlist they point to,
+    /// unless [`ConverterBuilder::strict_platform`] is set.
+    pub fn scrape(&self, url: &Url) -> anyhow::Result<Playlist> {
+        let resolved;
+        let url = if is_short_link(url) && !self.options.strict_platform {
+            resolved = resolve_short_link(&self.browser, url, &self.options)?;
+            log::info!("Resolved {url} to {resolved}");
+            &resolved
+        } else {
+            url
+        };
+        // Single videos and songs convert on their own, as a playlist of one
+        if let Some(id) = youtube_video_id(url) {
+            return scrape::fetch_video(&self.browser, url, &id, &self.options);
+        }
+        let source_platform = Platform::from_url(url);
+        let is_song = link_id(url).is_some_and(|id| matches!(id.kind.as_str(), "track" | "song"));
+        let playlist = match source_platform {
+            Platform::Spotify | Platform::Apple if is_song => {
+                scrape::fetch_song(&self.browser, url, source_platform, &self.options)
+            }
+            Platform::Youtube => scrape::fetch_yt_playlist(&self.browser, url, &self.options),
+            Platform::Spotify => scrape::fetch_spotify_playlist(&self.browser, url, &self.options),
+            Platform::Apple => scrape::fetch_apple_playlist(&self.browser, url, &self.options),
+            platform => Err(UnsupportedPlatform::Source(platform).into()),
+        }?;
+        Ok(playlist)
+    }
+
+    /// Finds `tracks` on the `target` platform, `None` marks songs that weren't found
+    ///
+    /// Each resolver tries the tracks the ones before it missed, see [`Converter::with_resolver`].
+    pub fn convert(
+        &self,
+        tracks: &[Track],
+        target: &Platform,
+    ) -> anyhow::Result<Vec<Option<Match>>> {
+        // Songs already on the target's provider keep their exact ID, no search needed
+        let mut matches = tracks
+            .iter()
+            .map(|track| {
+                let link = same_provider_link(track.source_url.as_deref()?, target)?;
+                Some(Match {
+                    link,
+                    confidence: 1.0,
+                    query: SearchQuery::default(),
+                })
+            })
+            .collect::<Vec<_>>();
+        if self.options.event_stream {
+            for (track, found) in tracks.iter().zip(&matches) {
+                if let Some(found) = found {
+                    Event::Converted {
+                        track,
+                        link: &found.link,
+                        confidence: found.confidence,
+                    }
+                    .emit();
+                }
+            }
+        }
+        let unmatched = (0..tracks.len())
+            .filter(|&idx| matches[idx].is_none())
+            .collect::<Vec<_>>();
+        if unmatched.len() < tracks.len() {
+            log::info!(
+                "Found {} links without a search",
+                tracks.len() - unmatched.len()
+            );
+        }
+        if unmatched.is_empty() {
+            return Ok(matches);
+        }
+
+        let browser = BrowserSearch::new(&self.browser, &self.options);
+        let resolvers = self.resolvers(&browser);
+        let resolvers = resolvers
+            .iter()
+            .filter(|resolver| resolver.supports(target))
+            .collect::<Vec<_>>();
+        if resolvers.is_empty() {
+            return Err(UnsupportedPlatform::Target(*target).into());
+        }
+
+        let mut misses = (0..tracks.len()).map(|_| None).collect::<Vec<_>>();
+        for resolver in resolvers {
+            let pending = (0..tracks.len())
+                .filter(|&idx| matches[idx].is_none())
+                .collect::<Vec<_>>();
+            if pending.is_empty() {
+                break;
+            }
+            let mut results = (0..tracks.len()).map(|_| None).collect::<Vec<_>>();
+            let tagged = self.convert_pending(*resolver, &browser, tracks, target, &pending)?;
+            place_by_index(&mut results, tagged);
+            for (idx, result) in results.into_iter().enumerate() {
+                match result {
+                    Some(Ok(found)) => matches[idx] = Some(found),
+                    Some(Err(e)) => misses[idx] = Some(e),
+                    None => {}
+                }
+            }
+        }
+
+        for idx in unmatched {
+            let (Some(e), None) = (&misses[idx], &matches[idx]) else {
+                continue;
+            };
+            let track = &tracks[idx];
+            log::warn!(
+                event = "track_unmatched",
+                position = idx + 1,
+                name = track.name.as_str();
+                "[{}/{}] Url not found for {}: {e}", idx + 1, tracks.len(), track.name
+            );
+            if self.options.event_stream {
+                Event::Unmatched {
+                    track,
+                    reason: e.to_string(),
+                }
+                .emit();
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Resolvers in the order they're tried, the browser search alone unless others were added
+    fn resolvers<'b>(&'b self, browser: &'b BrowserSearch<'_>) -> Vec<&'b dyn TrackResolver> {
+        if self.resolvers.is_empty() {
+            return vec![browser];
+        }
+        self.resolvers
+            .iter()
+            .map(|step| match step {
+                ResolverStep::Browser => browser as &dyn TrackResolver,
+                ResolverStep::Added(resolver) => resolver.as_ref(),
+            })
+            .collect()
+    }
+
+    /// Looks the `pending` tracks up with `resolver` in parallel, each result tagged with the
+    /// track's index
+    fn convert_pending(
+        &self,
+        resolver: &dyn TrackResolver,
+        browser: &BrowserSearch,
+        tracks: &[Track],
+        target: &Platform,
+        pending: &[usize],
+    ) -> anyhow::Result<Vec<(usize, Option<anyhow::Result<Match>>)>> {
+        let reporter = Reporter::new(
+            self.options.progress,
+            "converted",
+            Some(pending.len() as u64),
+        );
+        let reporter = &reporter;
+
+        // Workers take the next track as they free up, so slow searches don't hold back a whole
+        // chunk, and tag every result with its index to restore the playlist order
+        let next = AtomicUsize::new(0);
+        let next = &next;
+        let tagged = thread::scope(|scope| {
+            let workers = (0..self.options.concurrency.min(pending.len()))
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut found = vec![];
+                        loop {
+                            let position = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(&idx) = pending.get(position) else {
+                                return found;
+                            };
+                            let track = &tracks[idx];
+                            let progress = (position + 1, pending.len());
+                            let converted =
+                                self.convert_track(resolver, browser, track, target, progress);
+                            found.push((idx, Some(converted)));
+                            reporter.advance(1);
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .map_err(|_| anyhow!("Conversion worker panicked"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })?;
+        reporter.finish();
+        Ok(tagged.into_iter().flatten().collect())
+    }
+
+    /// Retries the unmatched `tracks` with progressively looser matching, filling `matches` in
+    /// place
+    ///
+    /// Returns the index of every newly matched track with the 1-based relaxation level that
+    /// found it.
+    pub fn retry_unmatched(
+        &self,
+        tracks: &[Track],
+        target: &Platform,
+        matches: &mut [Option<Match>],
+    ) -> anyhow::Result<Vec<(usize, usize)>> {
+        let browser = BrowserSearch::new(&self.browser, &self.options);
+        let resolvers = self.resolvers(&browser);
+        let resolvers = resolvers
+            .iter()
+            .filter(|resolver| resolver.supports(target))
+            .collect::<Vec<_>>();
+        if resolvers.is_empty() {
+            return Err(UnsupportedPlatform::Target(*target).into());
+        }
+        let mut recovered = vec![];
+
+        for (level, relaxation) in RELAXATIONS.iter().enumerate() {
+            let options = relaxation.options(&self.options);
+            for (idx, track) in tracks.iter().enumerate() {
+                let query = relaxation.query(track);
+                for resolver in &resolvers {
+                    if matches[idx].is_some() {
+                        break;
+                    }
+                    match self.find_match(**resolver, &browser, &query, target, &options) {
+                        Ok(found) => {
+                            log::info!(
+                                "Relaxation {} found {}: {}",
+                                level + 1,
+                                track.name,
+                                found.link
+                            );
+                            if self.options.event_stream {
+                                Event::Converted {
+                                    track,
+                                    link: &found.link,
+                                    confidence: found.confidence,
+                                }
+                                .emit();
+                            }
+                            matches[idx] = Some(found);
+                            recovered.push((idx, level + 1));
+                        }
+                        Err(e) => {
+                            log::debug!("Relaxation {} missed {}: {e}", level + 1, track.name)
+                        }
+                    }
+                }
+            }
+        }
+        Ok(recovered)
+    }
+
+    /// Replaces the album, duration and year of matched `tracks` with the ones their page on the
+    /// `target` platform shows, a navigation per track
+    ///
+    /// Tracks whose page fails to load keep their scraped fields.
+    pub fn enrich(
+        &self,
+        tracks: &mut [Track],
+        matches: &[Option<Match>],
+        target: &Platform,
+    ) -> anyhow::Result<()> {
+        let lookup = resolve::lookup(target)?;
+        let reporter = Reporter::new(
+            self.options.progress,
+            "enriched",
+            Some(matches.iter().flatten().count() as u64),
+        );
+        let mut tab = WorkerTab::new(&self.browser, &self.options);
+        for (track, found) in tracks.iter_mut().zip(matches) {
+            let Some(found) = found else {
+                continue;
+            };
+            let metadata = tab
+                .get()
+                .and_then(|tab| lookup(&tab, &found.link, &self.options));
+            tab.record(&metadata);
+            match metadata {
+                Ok(metadata) => metadata.apply(track),
+                Err(e) => log::warn!("Failed to read the details of {}: {e:#}", found.link),
+            }
+            reporter.advance(1);
+        }
+        reporter.finish();
+        Ok(())
+    }
+
+    /// Opens every matched link and unmatches the ones that no longer lead to a song page, a
+    /// navigation per link spread over the configured tabs
+    ///
+    /// Returns the index of every unmatched track with its dead link.
+    pub fn verify_links(
+        &self,
+        matches: &mut [Option<Match>],
+    ) -> anyhow::Result<Vec<(usize, String)>> {
+        let links = matches
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, found)| Some((idx, found.as_ref()?.link.clone())))
+            .collect::<Vec<_>>();
+        if links.is_empty() {
+            return Ok(vec![]);
+        }
+        let reporter = Reporter::new(self.options.progress, "verified", Some(links.len() as u64));
+        let reporter = &reporter;
+        let links = &links;
+
+        let next = AtomicUsize::new(0);
+        let next = &next;
+        let dead = thread::scope(|scope| {
+            let workers = (0..self.options.concurrency.min(links.len()))
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut tab = WorkerTab::new(&self.browser, &self.options);
+                        let mut dead = vec![];
+                        while let Some((idx, link)) =
+                            links.get(next.fetch_add(1, Ordering::Relaxed))
+                        {
+                            let loaded = tab
+                                .get()
+                                .and_then(|tab| check_song_page(&tab, link, &self.options));
+                            tab.record(&loaded);
+                            if let Err(e) = loaded {
+                                log::warn!("{link} didn't load a song: {e:#}");
+                                dead.push((*idx, link.clone()));
+                            }
+                            reporter.advance(1);
+                        }
+                        dead
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .map_err(|_| anyhow!("Verification worker panicked"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })?;
+        reporter.finish();
+
+        let mut dead = dead.into_iter().flatten().collect::<Vec<_>>();
+        dead.sort();
+        for (idx, _) in &dead {
+            matches[*idx] = None;
+        }
+        Ok(dead)
+    }
+
+    /// Probes a search page of the platform for an element its scrapers rely on
+    ///
+    /// Fails with [`LayoutChanged`] when the element is missing, before a long scrape runs into
+    /// the same wall. Platforms without built-in selectors pass.
+    pub fn check_layout(&self, platform: &Platform) -> anyhow::Result<()> {
+        let Some(probe) = LAYOUT_PROBES
+            .iter()
+            .find(|probe| probe.platform == *platform)
+        else {
+            return Ok(());
+        };
+        let tab = open_tab(&self.browser, &self.options)?;
+        navigate_with(&tab, probe.url, WaitStrategy::Navigated, &self.options)?;
+        let found = tab.wait_for_element(probe.sentinel).is_ok();
+        if let Err(e) = tab.close(true) {
+            log::error!("Failed to close tab with {e:?}")
+        }
+        if !found {
+            return Err(LayoutChanged {
+                platform: probe.platform,
+                version: probe.version,
+            }
+            .into());
+        }
+        log::info!(
+            "{platform:?} layout matches the selectors of {}",
+            probe.version
+        );
+        Ok(())
+    }
+
+    /// Lists the `target` platform's results for `track` with their scores, most confident first
+    pub fn explain(
+        &self,
+        track: &Track,
+        target: &Platform,
+    ) -> anyhow::Result<Vec<(Candidate, Score)>> {
+        let candidates = BrowserSearch::new(&self.browser, &self.options).search(track, target)?;
+        Ok(resolve::rank(candidates, track, self.options.similarity))
+    }
+
+    /// Resolves a single track with `resolver`, logging a match with its `(position, total)`
+    /// progress
+    fn convert_track(
+        &self,
+        resolver: &dyn TrackResolver,
+        browser: &BrowserSearch,
+        track: &Track,
+        target: &Platform,
+        (position, total): (usize, usize),
+    ) -> anyhow::Result<Match> {
+        let found = self.find_match(resolver, browser, track, target, &self.options);
+        match &found {
+            Ok(found) => {
+                log::info!(
+                    event = "track_converted",
+                    position,
+                    name = track.name.as_str(),
+                    link = found.link.as_str(),
+                    confidence = found.confidence,
+                    title_only = found.query == SearchQuery::TitleOnly;
+                    "[{position}/{total}] Song: {:#?}", found.link
+                );
+                if self.options.event_stream {
+                    Event::Converted {
+                        track,
+                        link: &found.link,
+                        confidence: found.confidence,
+                    }
+                    .emit();
+                }
+            }
+            Err(e) => log::info!("{} missed {}: {e}", resolver.name(), track.name),
+        }
+        found
+    }
+
+    /// Searches title and artist, then the title alone if allowed and the first search missed
+    fn find_match(
+        &self,
+        resolver: &dyn TrackResolver,
+        browser: &BrowserSearch,
+        track: &Track,
+        target: &Platform,
+        options: &Options,
+    ) -> anyhow::Result<Match> {
+        let track = &options.artist_selection.select(track);
+        if is_ambiguous_title(&track.name) {
+            match options.ambiguous_policy {
+                AmbiguousPolicy::Skip => {
+                    return Err(anyhow!("Title is too ambiguous to match, skipped"))
+                }
+                AmbiguousPolicy::Interactive => {
+                    return Err(anyhow!("Title is too ambiguous to match, left for review"))
+                }
+                AmbiguousPolicy::Strict => {}
+            }
+        }
+        let combined = self.search_and_pick(
+            resolver,
+            browser,
+            track,
+            target,
+            SearchQuery::TitleAndArtist,
+            options,
+        );
+        let found = match combined {
+            Err(e) if options.title_only_fallback => {
+                log::debug!("Searching the title of {} alone after: {e}", track.name);
+                self.search_and_pick(
+                    resolver,
+                    browser,
+                    track,
+                    target,
+                    SearchQuery::TitleOnly,
+                    options,
+                )
+            }
+            combined => combined,
+        };
+        match found {
+            Err(e) if options.swap_fallback => {
+                log::debug!(
+                    "Searching {} with name and artist swapped after: {e}",
+                    track.name
+                );
+                let swapped = self.search_and_pick(
+                    resolver,
+                    browser,
+                    track,
+                    target,
+                    SearchQuery::Swapped,
+                    options,
+                )?;
+                if swapped.confidence < options.min_confidence {
+                    return Err(anyhow!(
+                        "Swapped search only found {} at {:.2}",
+                        swapped.link,
+                        swapped.confidence
+                    ));
+                }
+                log::info!(
+                    "Matched {} — {} with name and artist swapped",
+                    track.name,
+                    track.artist
+                );
+                Ok(swapped)
+            }
+            found => found,
+        }
+    }
+
+    /// Searches `track` as `query` with `resolver`, picking among the results like any other
+    fn search_and_pick(
+        &self,
+        resolver: &dyn TrackResolver,
+        browser: &BrowserSearch,
+        track: &Track,
+        target: &Platform,
+        query: SearchQuery,
+        options: &Options,
+    ) -> anyhow::Result<Match> {
+        let searched = query.query(track);
+        // Swapped searches are scored as swapped, the others against the full track
+        let original = if query == SearchQuery::Swapped {
+            &searched
+        } else {
+            track
+        };
+        let track = &searched;
+        let mut candidates = resolver.search(track, target)?;
+        if query == SearchQuery::TitleOnly {
+            // Without the artist in the query the results hold any song of that title
+            candidates.retain(|candidate| {
+                Score::of(candidate, original, options.similarity).artist
+                    >= options.artist_threshold
+            });
+        }
+        resolve::drop_blocked(&mut candidates, original, &options.artist_blocklist);
+        options
+            .ambiguous_policy
+            .filter(&mut candidates, original, options.similarity);
+        options
+            .album_mode
+            .filter(&mut candidates, original, options.similarity);
+        resolve::prefer_year(&mut candidates, original, options.match_year);
+        let considered = (options.matcher == Matcher::Scored)
+            .then_some(options.considered.as_ref())
+            .flatten()
+            .map(|considered| (considered, candidates.clone()));
+        let candidate = options.matcher.pick(candidates, original, options);
+        if let Some((considered, candidates)) = considered {
+            let chosen = candidate.as_ref().map(|candidate| candidate.link.as_str());
+            let rows = resolve::rank(candidates, original, options.similarity)
+                .into_iter()
+                .map(|(candidate, score)| ConsideredCandidate {
+                    track: track.clone(),
+                    query,
+                    chosen: chosen == Some(candidate.link.as_str()),
+                    candidate,
+                    score,
+                });
+            considered
+                .lock()
+                .expect("Candidate log isn't poisoned")
+                .extend(rows);
+        }
+        let candidate = candidate.ok_or_else(|| anyhow!("Song not found"))?;
+        if let (true, Some(year), None) = (options.match_year, original.year, candidate.year) {
+            self.check_year(browser, &candidate.link, year)?;
+        }
+        Ok(Match {
+            confidence: Score::of(&candidate, original, options.similarity).confidence(),
+            link: candidate.link,
+            query,
+        })
+    }
+
+    /// Fails unless the song page behind `link` shows the release `year`
+    fn check_year(&self, browser: &BrowserSearch, link: &str, year: u16) -> anyhow::Result<()> {
+        let platform = Url::parse(link).map(|url| Platform::from_url(&url))?;
+        let lookup = resolve::lookup(&platform)?;
+        let metadata = browser.with_tab(|tab| {
+            let metadata = tab
+                .get()
+                .and_then(|current| lookup(&current, link, &self.options));
+            tab.record(&metadata);
+            metadata
+        });
+        match metadata?.year {
+            Some(found) if found == year => Ok(()),
+            Some(found) => Err(anyhow!("Song found from {found}, expected {year}")),
+            None => Err(anyhow!("No release year on {link}")),
+        }
+    }
+}
+
+/// The target platform's search page read in browser tabs, the resolver every converter has
+///
+/// A search takes a free tab, or opens one, and hands it back once done, so the workers keep
+/// reusing warm tabs.
+struct BrowserSearch<'a> {
+    browser: &'a Browser,
+    options: &'a Options,
+    tabs: TabPool<WorkerTab<'a>>,
+}
+
+impl<'a> BrowserSearch<'a> {
+    fn new(browser: &'a Browser, options: &'a Options) -> Self {
+        Self {
+            browser,
+            options,
+            tabs: TabPool::new(options.tab_slots.capacity()),
+        }
+    }
+
+    /// Runs `f` with a free tab of the pool
+    fn with_tab<T>(&self, f: impl FnOnce(&mut WorkerTab<'a>) -> T) -> T {
+        self.tabs
+            .with(|| WorkerTab::new(self.browser, self.options), f)
+    }
+}
+
+impl TrackResolver for BrowserSearch<'_> {
+    fn name(&self) -> &str {
+        "browser"
+    }
+
+    fn supports(&self, target: &Platform) -> bool {
+        resolve::searcher(target).is_ok()
+    }
+
+    fn search(&self, track: &Track, target: &Platform) -> anyhow::Result<Vec<Candidate>> {
+        let search = resolve::searcher(target)?;
+        let options = self.options;
+        self.with_tab(|tab| {
+            let candidates = match options.track_timeout {
+                Some(timeout) => {
+                    let current = tab.get()?;
+                    match search_with_deadline(current.clone(), search, track, options, timeout) {
+                        Some(candidates) => screenshot_on_error(&current, options, candidates),
+                        None => {
+                            // The hung search keeps the tab busy, closing it also ends the search; a
+                            // screenshot would wait on the same hung page
+                            tab.recycle();
+                            return Err(anyhow!("Search didn't finish within {timeout:?}"));
+                        }
+                    }
+                }
+                None => {
+                    let current = tab.get()?;
+                    screenshot_on_error(&current, options, search(&current, track, options))
+                }
+            };
+            tab.record(&candidates);
+            candidates
+        })
+    }
+}
+
+/// Puts the results tagged with their playlist index back in their slots, in whatever order the
+/// workers finished them
+fn place_by_index<T>(
+    slots: &mut [Option<T>],
+    tagged: impl IntoIterator<Item = (usize, Option<T>)>,
+) {
+    for (idx, found) in tagged {
+        slots[idx] = found;
+    }
+}
+
+/// Fails unless `link` still opens a song, not a home page it redirected to or an error page
+fn check_song_page(tab: &Tab, link: &str, options: &Options) -> anyhow::Result<()> {
+    navigate_with(tab, link, WaitStrategy::Navigated, options)?;
+    let landed = Url::parse(&tab.get_url())?;
+    if !link_id(&landed).is_some_and(|id| matches!(id.kind.as_str(), "track" | "song")) {
+        return Err(anyhow!("Redirected to {landed}"));
+    }
+    let text = tab
+        .find_element("body")
+        .and_then(|body| body.get_inner_text())
+        .unwrap_or_default()
+        .to_lowercase();
+    match DEAD_PAGE_MESSAGES
+        .iter()
+        .find(|message| text.contains(*message))
+    {
+        Some(message) => Err(anyhow!("The page says \"{message}\"")),
+        None => Ok(()),
+    }
+}
+
+/// Runs the search on its own thread so a hanging page can be abandoned, `None` on timeout
+fn search_with_deadline(
+    tab: Arc<Tab>,
+    search: Search,
+    track: &Track,
+    options: &Options,
+    timeout: Duration,
+) -> Option<anyhow::Result<Vec<Candidate>>> {
+    let (sender, receiver) = mpsc::channel();
+    let (track, options) = (track.clone(), options.clone());
+    thread::spawn(move || {
+        // The receiver is gone when the deadline passed first
+        let _ = sender.send(search(&tab, &track, &options));
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(candidates) => Some(candidates),
+        Err(mpsc::RecvTimeoutError::Timeout) => None,
+        Err(mpsc::RecvTimeoutError::Disconnected) => Some(Err(anyhow!("Search worker panicked"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_finished_out_of_order_keep_the_playlist_order() {
+        let mut slots = vec![Some("kept"), None, None, None, None];
+        let finished = [(3, Some("d")), (1, Some("b")), (4, None), (2, Some("c"))];
+        place_by_index(&mut slots, finished);
+        assert_eq!(slots, [Some("kept"), Some("b"), Some("c"), Some("d"), None]);
+    }
+}
@@ -0,0 +1,35 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::Track;
+
+/// Line of the `ndjson-stream` output, tagged with its `type`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// A scraper found a track
+    Scraped { track: &'a Track },
+    /// A track was found on the target platform, an earlier `unmatched` line for it is
+    /// superseded when `--retry-unmatched` or the interactive review finds it
+    Converted {
+        track: &'a Track,
+        link: &'a str,
+        confidence: f64,
+    },
+    /// Nothing on the target platform matched a track, or `--verify-links` found its link dead
+    Unmatched { track: &'a Track, reason: String },
+    /// The command failed
+    Error { message: String },
+}
+
+impl Event<'_> {
+    /// Writes the event as one JSON line to stdout, locked so parallel workers don't interleave
+    pub fn emit(&self) {
+        let line = serde_json::to_string(self).expect("Events serialize to JSON");
+        let mut stdout = io::stdout().lock();
+        if let Err(e) = writeln!(stdout, "{line}").and_then(|()| stdout.flush()) {
+            log::error!("Failed to write an event to stdout: {e}");
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Attempts made for a request the API keeps answering with 429
+const MAX_ATTEMPTS: u32 = 5;
+
+/// HTTP client shared by every API-based scraper and matcher
+///
+/// All requests go through one rate limiter, and `429 Too Many Requests` responses are
+/// retried after the delay from their `Retry-After` header.
+pub struct ApiClient {
+    agent: ureq::Agent,
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl ApiClient {
+    /// Creates a client sending at most `requests_per_second` requests
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+            interval: Duration::from_secs_f64(1.0 / requests_per_second.max(0.01)),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Starts a request, send it with [`ApiClient::send`]
+    pub fn request(&self, method: &str, url: &str) -> ureq::Request {
+        self.agent.request(method, url)
+    }
+
+    /// Sends `request` with an optional JSON body, waiting for the rate limiter first
+    pub fn send(
+        &self,
+        request: ureq::Request,
+        body: Option<&serde_json::Value>,
+    ) -> anyhow::Result<ureq::Response> {
+        for attempt in 1.. {
+            self.wait_turn();
+            let response = match body {
+                Some(body) => request.clone().send_json(body),
+                None => request.clone().call(),
+            };
+
+            match response {
+                Err(ureq::Error::Status(429, response)) if attempt < MAX_ATTEMPTS => {
+                    let delay = retry_after(&response).unwrap_or(Duration::from_secs(1 << attempt));
+                    log::warn!("Rate limited by {}, retrying in {delay:?}", request.url());
+                    thread::sleep(delay);
+                }
+                response => return Ok(response?),
+            }
+        }
+        unreachable!("Attempts are unbounded")
+    }
+
+    fn wait_turn(&self) {
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if *next_slot > now {
+            thread::sleep(*next_slot - now);
+        }
+        *next_slot = now.max(*next_slot) + self.interval;
+    }
+}
+
+/// Delay requested by the server, only the delay-seconds form is supported
+fn retry_after(response: &ureq::Response) -> Option<Duration> {
+    response
+        .header("Retry-After")?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,66 @@
+//! Scrapes music playlists and converts them between platforms
+//!
+//! The [`Converter`] is the entry point, configured through [`ConverterBuilder`]:
+//!
+//! ```no_run
+//! use unilist::{Converter, Platform};
+//! use url::Url;
+//!
+//! let converter = Converter::builder().region("gb").build()?;
+//! let url = Url::parse("https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M")?;
+//! let playlist = converter.scrape(&url)?;
+//! let matches = converter.convert(&playlist.tracks, &Platform::Apple)?;
+//! # anyhow::Ok(())
+//! ```
+
+mod browser;
+mod converted;
+mod converter;
+mod events;
+mod http;
+mod link;
+mod normalize;
+mod output;
+mod platform;
+mod playlist;
+mod progress;
+mod resolve;
+mod scrape;
+mod selectors;
+mod spotify_api;
+mod track;
+mod validate;
+
+pub use browser::{BrowserNotFound, WaitStrategy};
+pub use converted::{ConvertedLinks, ConvertedTrack, Mapping};
+pub use converter::{Converter, ConverterBuilder};
+
+/// Pure parsing and matching paths, exposed for the throughput benchmarks
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::{
+        resolve::rank,
+        scrape::{parse_apple_recording, parse_apple_row, parse_spotify_row, parse_yt_row},
+    };
+}
+pub use events::Event;
+pub use http::ApiClient;
+pub use link::{
+    absolute_link, bundle_links, is_short_link, link_id, normalize_link, same_provider_link,
+    spotify_id, spotify_track_uri, youtube_link, youtube_video_id, LinkId,
+};
+pub use normalize::{normalize_artist, normalize_title, split_artists};
+pub use output::{html_report, match_report_csv, ArtistFormat, CsvFormat, OutputTemplate};
+pub use platform::{Platform, UnsupportedPlatform};
+pub use playlist::{PartialScrape, Playlist, PlaylistError};
+pub use progress::Progress;
+pub use resolve::{
+    is_ambiguous_title, search_url, AlbumMode, AmbiguousPolicy, ArtistSelection, Candidate,
+    ConsideredCandidate, Match, Matcher, Score, SearchQuery, Similarity, TrackResolver,
+    VersionPreference, DEFAULT_ARTIST_BLOCKLIST,
+};
+pub use selectors::{AppleSelectors, LayoutChanged, Selectors, SpotifySelectors};
+pub use spotify_api::{CreatedPlaylist, SpotifyApi, SpotifyApiResolver};
+pub use track::{Track, TrackKey};
+pub use validate::{validate, TrackIssue};
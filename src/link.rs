@@ -0,0 +1,218 @@
+use url::Url;
+
+use crate::Platform;
+
+/// Query parameters that only carry tracking or UI state
+///
+/// Apple's `i` is kept, it selects the song in `/album/...?i=` links.
+const TRACKING_PARAMS: &[&str] = &[
+    "si",
+    "context",
+    "nd",
+    "dl_branch",
+    "feature",
+    "pp",
+    "l",
+    "ls",
+    "app",
+    "at",
+    "ct",
+    "itscg",
+    "itsct",
+    "uo",
+];
+
+/// Strips tracking parameters from `link`, leaving it untouched if it isn't a URL
+pub fn normalize_link(link: &str) -> String {
+    let Ok(mut url) = Url::parse(link) else {
+        return link.to_string();
+    };
+
+    let kept = url
+        .query_pairs()
+        .filter(|(key, _)| !TRACKING_PARAMS.contains(&key.as_ref()) && !key.starts_with("utm_"))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    url.set_fragment(None);
+    url.into()
+}
+
+/// Hosts of share links that redirect to a platform page
+const SHORT_LINK_HOSTS: &[&str] = &["spotify.link", "spoti.fi", "apple.co"];
+
+/// Whether the link is a share link that only reveals its platform after redirecting, like
+/// `https://spotify.link/...` or `https://music.apple.com/{region}/l/...`
+pub fn is_short_link(url: &Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    let apple_short = host == "music.apple.com"
+        && url
+            .path_segments()
+            .is_some_and(|mut segments| segments.nth(1) == Some("l"));
+    SHORT_LINK_HOSTS.contains(&host) || apple_short
+}
+
+/// Kind and ID of the resource a platform link points to
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkId {
+    /// e.g. `playlist`, `album` or `song`
+    pub kind: String,
+    pub id: String,
+}
+
+/// Extracts the resource a link points to, `None` for unknown hosts or paths
+///
+/// Apple `/album/...?i=ID` links point to the song, not the album.
+pub fn link_id(url: &Url) -> Option<LinkId> {
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    };
+    let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+    let (kind, id) = match Platform::from_url(url) {
+        Platform::Spotify => {
+            let kind = segments.find(|s| !s.starts_with("intl-"))?;
+            (kind.to_string(), segments.next()?.to_string())
+        }
+        Platform::Youtube => match segments.next()? {
+            "playlist" => ("playlist".to_string(), query("list")?),
+            "watch" => ("track".to_string(), query("v")?),
+            kind @ ("browse" | "channel") => (kind.to_string(), segments.next()?.to_string()),
+            _ => return None,
+        },
+        Platform::Apple => {
+            // `/{region}/{kind}/{slug}/{id}`, the slug is missing from some short forms
+            let kind = segments.nth(1)?;
+            let id = segments.next_back()?.to_string();
+            match query("i") {
+                Some(song) if kind == "album" => ("song".to_string(), song),
+                _ => (kind.to_string(), id),
+            }
+        }
+        Platform::Unknown => return None,
+    };
+    Some(LinkId { kind, id })
+}
+
+/// Turns an `https://open.spotify.com/track/ID` link into a `spotify:track:ID` URI
+pub fn spotify_track_uri(link: &str) -> Option<String> {
+    spotify_id(link, "track").map(|id| format!("spotify:track:{id}"))
+}
+
+/// Extracts the ID from an `https://open.spotify.com/{kind}/ID` link
+pub fn spotify_id(link: &str, kind: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    if url.host_str() != Some("open.spotify.com") {
+        return None;
+    }
+
+    let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+    // Localized links carry a leading `intl-xx` segment
+    let link_kind = segments.find(|s| !s.starts_with("intl-"))?;
+    let id = segments.next()?;
+    (link_kind == kind).then(|| id.to_string())
+}
+
+/// Longest bundled link, browsers and chat apps truncate longer ones
+const MAX_BUNDLE_LINK_LEN: usize = 2000;
+
+/// Packs track links into as few links opening all of them at once as the URL length allows
+///
+/// Only Spotify has such links, `trackset` pages listing up to a few hundred IDs. `None` for
+/// targets without them.
+pub fn bundle_links(target: &Platform, name: &str, links: &[String]) -> Option<Vec<String>> {
+    if *target != Platform::Spotify {
+        return None;
+    }
+    let base = format!(
+        "https://open.spotify.com/trackset/{}/",
+        urlencoding::encode(name)
+    );
+    let mut bundles = vec![];
+    let mut current = base.clone();
+    for id in links.iter().filter_map(|link| spotify_id(link, "track")) {
+        if current.len() > base.len() && current.len() + 1 + id.len() > MAX_BUNDLE_LINK_LEN {
+            bundles.push(std::mem::replace(&mut current, base.clone()));
+        }
+        if current.len() > base.len() {
+            current.push(',');
+        }
+        current.push_str(&id);
+    }
+    if current.len() > base.len() {
+        bundles.push(current);
+    }
+    Some(bundles)
+}
+
+/// Hosts of regular YouTube video links, whose IDs YouTube Music shares
+const YOUTUBE_VIDEO_HOSTS: &[&str] = &["www.youtube.com", "youtube.com", "m.youtube.com"];
+
+/// Video ID of a YouTube or YouTube Music `watch?v=ID` link, or of a `youtu.be/ID` share link
+pub fn youtube_video_id(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    if host == "youtu.be" {
+        return url
+            .path_segments()?
+            .next()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+    }
+    if host != "music.youtube.com" && !YOUTUBE_VIDEO_HOSTS.contains(&host) {
+        return None;
+    }
+    if url.path() != "/watch" {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "v")
+        .map(|(_, id)| id.into_owned())
+}
+
+/// Rewrites a link to a song already on the `target`'s provider instead of searching for it
+///
+/// Only YouTube shares its IDs across hosts: video links become YouTube Music ones.
+pub fn same_provider_link(link: &str, target: &Platform) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    match target {
+        Platform::Youtube => youtube_video_id(&url).map(|id| youtube_link(&id, true)),
+        _ => None,
+    }
+}
+
+/// Link to the video on YouTube Music, or on regular YouTube if `music` is false
+pub fn youtube_link(id: &str, music: bool) -> String {
+    let host = if music {
+        "music.youtube.com"
+    } else {
+        "www.youtube.com"
+    };
+    format!("https://{host}/watch?v={}", urlencoding::encode(id))
+}
+
+/// Resolves an `href` read from a page against the page's URL, so relative paths become full links
+///
+/// ```
+/// use unilist::absolute_link;
+/// use url::Url;
+///
+/// let page = Url::parse("https://music.apple.com/us/search?term=song")?;
+/// assert_eq!(
+///     absolute_link(&page, "/us/album/record/123?i=456").as_deref(),
+///     Some("https://music.apple.com/us/album/record/123?i=456")
+/// );
+/// assert_eq!(
+///     absolute_link(&page, "https://music.apple.com/us/song/song/456").as_deref(),
+///     Some("https://music.apple.com/us/song/song/456")
+/// );
+/// # Ok::<(), url::ParseError>(())
+/// ```
+pub fn absolute_link(page: &Url, href: &str) -> Option<String> {
+    Some(page.join(href.trim()).ok()?.into())
+}
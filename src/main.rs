@@ -1,275 +1,1995 @@
-use std::{fs, time::Duration};
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    fmt, fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, IsTerminal, Write},
+    mem,
+    process::{self, ExitCode},
+    thread,
+    time::Duration,
+};
 
-use anyhow::anyhow;
-use headless_chrome::{Browser, LaunchOptions, Tab};
-use serde::{Deserialize, Serialize};
-
-use clap::{Parser, ValueEnum};
-use std::path::PathBuf;
-use url::Url;
+use anyhow::{anyhow, bail, Context};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use log::kv::{self, Key, VisitSource};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use unilist::{
+    bundle_links, html_report, is_ambiguous_title, is_short_link, link_id, match_report_csv,
+    normalize_link, same_provider_link, search_url, spotify_id, spotify_track_uri, validate,
+    youtube_link, youtube_video_id, AlbumMode, AmbiguousPolicy, ArtistFormat, ArtistSelection,
+    BrowserNotFound, ConvertedLinks, Converter, CsvFormat, Event, LinkId, Mapping, Match, Matcher,
+    OutputTemplate, PartialScrape, Platform, Playlist, PlaylistError, Progress, SearchQuery,
+    Selectors, Similarity, SpotifyApi, SpotifyApiResolver, Track, UnsupportedPlatform,
+    VersionPreference, WaitStrategy, DEFAULT_ARTIST_BLOCKLIST,
+};
+use url::{ParseError, Url};
 
 /// CLI for converting music playlists between platforms
 #[derive(Parser, Debug)]
 #[command(
     author = "suzumenobu",
     version = "1.0",
-    about = "Converts music playlists between platforms and saves them to a JSON file"
+    about = "Converts music playlists between platforms and saves them to a JSON file",
+    after_help = "Exit codes:\n  0  all tracks were found\n  1  any other error\n  2  some tracks weren't found\n  3  the playlist couldn't be scraped\n  4  the platform isn't supported\n  5  a file couldn't be read or written\n  6  Chrome/Chromium isn't installed"
 )]
 struct Args {
-    /// Input source (either a URL or a JSON file)
-    #[arg(short, long)]
+    #[command(subcommand)]
+    command: Command,
+
+    #[command(flatten)]
+    browser: BrowserArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Collect the tracks of a playlist, printing them as JSON unless saved with --save
+    Scrape(SourceArgs),
+    /// Find the tracks of a playlist on another platform and print their links
+    Convert(Box<ConvertArgs>),
+    /// Print which tracks of a playlist a playlist of the target platform already has
+    Diff {
+        #[command(flatten)]
+        source: SourceArgs,
+
+        /// Playlist URL on the target platform
+        target: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Print the scored search results for a single "Song - Artist"
+    Explain {
+        /// Track searched, as "Song - Artist"
+        query: String,
+
+        /// Target platform searched (e.g., spotify, apple)
+        #[arg(short, long)]
+        dist: Platform,
+
+        /// Number of candidates printed
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+
+        #[command(flatten)]
+        matching: MatchArgs,
+    },
+    /// Print the platform and resource ID detected in a URL, without opening a browser
+    ExplainUrl { url: String },
+    /// Scrape a small public playlist on each platform to check Chrome, the network and the
+    /// scrapers before a real run
+    SelfTest {
+        /// Platforms tested, every supported one by default
+        #[arg(long, value_enum, value_delimiter = ',')]
+        platforms: Vec<Platform>,
+    },
+    /// Check a playlist file for empty fields, durations read as names and duplicates, without
+    /// opening a browser
+    DryValidate {
+        /// JSON, CSV or query list file, `-` reads queries from stdin
+        source: String,
+
+        /// How the file is read, guessed from its extension by default
+        #[arg(long, value_enum, default_value_t = SourceFormat::Auto)]
+        source_format: SourceFormat,
+    },
+}
+
+/// Browser and logging options shared by every command
+#[derive(clap::Args, Debug)]
+struct BrowserArgs {
+    /// Flag to open change browser headless mode
+    #[arg(long, global = true, default_value_t = false)]
+    show_browser: bool,
+
+    /// Browser profile directory reused across runs, e.g. to keep a session where a CAPTCHA was
+    /// solved with --show-browser
+    #[arg(long, global = true, value_name = "DIR")]
+    user_data_dir: Option<PathBuf>,
+
+    /// Open the platform's homepage and accept its cookies before the playlist, for fresh sessions
+    /// landing on consent or region pages
+    #[arg(long, global = true, default_value_t = false)]
+    warmup: bool,
+
+    /// Hide headless browser tells like navigator.webdriver from the pages
+    #[arg(long, global = true, default_value_t = false)]
+    stealth: bool,
+
+    /// Shape of the results on stdout
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Log line format, the level is still set with RUST_LOG
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// How progress is reported on stderr, plain lines by default when stdout isn't a terminal
+    #[arg(long, global = true, value_enum)]
+    progress: Option<Progress>,
+
+    /// What scrapers wait for after opening the playlist page
+    #[arg(long, global = true, value_enum, default_value_t = WaitStrategy::Navigated)]
+    wait: WaitStrategy,
+
+    /// Seconds to wait for page elements before giving up
+    #[arg(long, global = true, default_value_t = 20)]
+    timeout_secs: u64,
+
+    /// Chrome or Chromium binary to launch instead of the one found on the system
+    #[arg(long, global = true)]
+    chrome_path: Option<PathBuf>,
+
+    /// Download a known-good Chromium if none is installed
+    #[arg(long, global = true, default_value_t = false)]
+    fetch_browser: bool,
+
+    /// Extra Chrome flag, repeatable (e.g., --chrome-arg=--no-sandbox)
+    #[arg(long, global = true, allow_hyphen_values = true)]
+    chrome_arg: Vec<String>,
+
+    /// Proxy server for the browser (e.g., socks5://127.0.0.1:1080)
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+
+    /// Maximum requests per second sent to platform APIs
+    #[arg(long, global = true, default_value_t = 5.0)]
+    api_rate_limit: f64,
+
+    /// JSON file overriding the CSS selectors of the target resolvers
+    #[arg(long, global = true)]
+    selectors: Option<PathBuf>,
+
+    /// Save a screenshot into this directory whenever a page fails to scrape or search
+    #[arg(long, global = true, value_name = "DIR")]
+    screenshot_on_error: Option<PathBuf>,
+
+    /// Probe the platforms' pages on startup and warn if their layout may have changed since the
+    /// built-in selectors were written
+    #[arg(long, global = true, default_value_t = false)]
+    scraper_version_check: bool,
+}
+
+/// Where the tracks come from and how they are scraped
+#[derive(clap::Args, Debug)]
+struct SourceArgs {
+    /// Input source (a playlist, album or song URL, a JSON or CSV file, or a .txt file or - listing
+    /// one URL per line)
     source: String,
 
+    /// How the source is read, guessed from its form and extension by default
+    #[arg(long, value_enum, default_value_t = SourceFormat::Auto)]
+    source_format: SourceFormat,
+
+    /// Fail on any source URL of an unknown host instead of following it as a share link
+    #[arg(long, default_value_t = false)]
+    strict_platform: bool,
+
+    /// Skip unsupported or unparsable URLs of a URL list instead of failing
+    #[arg(long, default_value_t = false)]
+    skip_unsupported: bool,
+
+    /// Only scrape the URLs of a URL list on these platforms, e.g. `youtube,spotify`, skipping
+    /// the others
+    #[arg(long, value_enum, value_delimiter = ',')]
+    only_platforms: Vec<Platform>,
+
     /// Output JSON file to save the parsed playlist
     #[arg(long)]
     save: Option<PathBuf>,
 
+    /// Gzip the file written by --save, implied by a `.gz` extension; loading detects it either way
+    #[arg(long, requires = "save")]
+    save_compressed: bool,
+
+    /// Save the tracks scraped before a failure to --save instead of dropping them
+    #[arg(long, requires = "save")]
+    allow_partial_save: bool,
+
+    /// Give album rows without an artist the album's artist, pass false to keep them empty
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    artist_from_album_header: bool,
+
+    /// Keep the album art URL of each scraped track in the saved playlist
+    #[arg(long, default_value_t = false)]
+    cover_art: bool,
+
+    /// Print each track to stderr as soon as it's scraped
+    #[arg(long, default_value_t = false)]
+    stream_tracks: bool,
+
+    /// Keep songs the playlist repeats instead of scraping each song once
+    #[arg(long, default_value_t = false)]
+    keep_duplicates: bool,
+
+    /// Stop scraping once this many tracks are collected
+    #[arg(long)]
+    max_tracks: Option<usize>,
+
+    /// Number of consecutive scrolls without new tracks before the Spotify scrape stops
+    #[arg(long, default_value_t = 3)]
+    scroll_stall_threshold: usize,
+
+    /// Milliseconds between two Spotify scrolls, plus up to half as much at random
+    #[arg(long, default_value_t = 300)]
+    scroll_delay_ms: u64,
+
+    /// Stop the Spotify scrape after this many scrolls, warning that it may be incomplete
+    #[arg(long, value_name = "N")]
+    max_scroll_iterations: Option<usize>,
+
+    /// File saving the tracks of a Spotify scrape as it scrolls, a rerun of the same playlist
+    /// resumes from it and only adds new rows
+    #[arg(long, value_name = "PATH")]
+    scroll_checkpoint: Option<PathBuf>,
+}
+
+/// How songs are searched and picked on the target platform
+#[derive(clap::Args, Debug)]
+struct MatchArgs {
+    /// Storefront region used when searching the target platform
+    #[arg(long, default_value = "us")]
+    region: String,
+
+    /// Strategy for picking a song among the search results
+    #[arg(long, value_enum, default_value_t = Matcher::Exact)]
+    matcher: Matcher,
+
+    /// Fuzzy metric scoring the titles and artists
+    #[arg(long, value_enum, default_value_t = Similarity::JaroWinkler)]
+    similarity: Similarity,
+
+    /// How tracks with a one-word title of a few letters like "Go" are matched, `interactive`
+    /// asks which search result to take once the conversion ends, or leaves them unmatched when
+    /// stdin isn't a terminal
+    #[arg(long, value_enum, default_value_t = AmbiguousPolicy::Strict)]
+    ambiguous_policy: AmbiguousPolicy,
+
+    /// How the track's album weighs on its match: ignored, a tiebreaker that favors the same
+    /// album among similar scored results without ever rejecting one, or required
+    #[arg(long, value_enum, default_value_t = AlbumMode::Soft)]
+    album_mode: AlbumMode,
+
+    /// Artists of a track's line searched and scored, all collaborators or the first one
+    #[arg(long, value_enum, default_value_t = ArtistSelection::All)]
+    artist_selection: ArtistSelection,
+
+    /// Lowest confidence (0 to 1) accepted by the scored matcher
+    #[arg(long, default_value_t = 0.8)]
+    min_confidence: f64,
+
+    /// Version picked when explicit and clean songs match equally well
+    #[arg(long, value_enum, default_value_t = VersionPreference::Either)]
+    prefer: VersionPreference,
+
+    /// Lowest title similarity (0 to 1) accepted by the scored matcher
+    #[arg(long, default_value_t = 0.85)]
+    title_threshold: f64,
+
+    /// Lowest artist similarity (0 to 1) accepted by the scored matcher
+    #[arg(long, default_value_t = 0.7)]
+    artist_threshold: f64,
+
+    /// Only accept songs released in the track's year when the source shows one, checking the song
+    /// page if the search results don't
+    #[arg(long, default_value_t = false)]
+    match_year: bool,
+
+    /// Search the title alone when title and artist find nothing, keeping songs of a similar artist
+    #[arg(long, default_value_t = false)]
+    title_only_fallback: bool,
+
+    /// Search name and artist swapped when nothing else matched, for rows scraped with their
+    /// columns reversed
+    #[arg(long, default_value_t = false)]
+    swap_fallback: bool,
+
+    /// Drop candidates whose artist or channel contains this, ignoring case, on top of the
+    /// defaults like "karaoke" and "tribute" (repeatable)
+    #[arg(long, value_name = "SUBSTRING")]
+    artist_blocklist: Vec<String>,
+
+    /// File listing one blocked artist substring per line, like --artist-blocklist
+    #[arg(long, value_name = "PATH")]
+    artist_blocklist_file: Option<PathBuf>,
+
+    /// Only block the artists given with --artist-blocklist and --artist-blocklist-file
+    #[arg(long, default_value_t = false)]
+    no_default_artist_blocklist: bool,
+
+    /// Write a CSV with every candidate weighed for each converted track, its scores and whether it
+    /// was picked, to this file (needs --matcher scored)
+    #[arg(long, value_name = "PATH")]
+    match_report_csv: Option<PathBuf>,
+
+    /// Number of tabs searching the target platform in parallel
+    #[arg(long, default_value_t = 1)]
+    concurrency: usize,
+
+    /// Most browser tabs searching at once, extra workers wait for a free one
+    #[arg(long, default_value_t = 8)]
+    max_open_tabs: usize,
+
+    /// Seconds a single track's search may take before it's marked unmatched
+    #[arg(long)]
+    track_timeout_secs: Option<u64>,
+
+    /// Consecutive failed searches before a worker replaces its browser tab
+    #[arg(long, default_value_t = 3)]
+    tab_failure_threshold: usize,
+}
+
+/// Shape of the printed tracks
+#[derive(clap::Args, Debug)]
+struct OutputArgs {
+    /// Template for each printed track, placeholders: {name}, {artist}, {album}, {year},
+    /// {duration}, {cover}, {source}, {link}, {confidence}
+    #[arg(long, value_parser = OutputTemplate::parse)]
+    output_template: Option<OutputTemplate>,
+
+    /// Separator joining the printed artists of a track, in the plain output, the
+    /// {artist} placeholder, the HTML report and the CSV files, e.g. " & "
+    #[arg(long)]
+    artist_separator: Option<String>,
+
+    /// Print only the first artist of a track wherever its artists are printed
+    #[arg(long, conflicts_with = "artist_separator")]
+    primary_artist_only: bool,
+
+    /// Field separator of the written CSV files, e.g. ';' for spreadsheets in many European
+    /// locales
+    #[arg(long, default_value_t = ',')]
+    csv_delimiter: char,
+
+    /// Quote every field of the written CSV files, not only the ones that need it
+    #[arg(long, default_value_t = false)]
+    csv_always_quote: bool,
+
+    /// Start the written CSV files with a UTF-8 byte order mark so Excel reads them as UTF-8
+    #[arg(long, default_value_t = false)]
+    csv_bom: bool,
+}
+
+impl OutputArgs {
+    /// How the artists of a track are printed
+    fn artists(&self) -> ArtistFormat {
+        match (&self.artist_separator, self.primary_artist_only) {
+            (_, true) => ArtistFormat::Primary,
+            (Some(separator), false) => ArtistFormat::Joined(separator.clone()),
+            (None, false) => ArtistFormat::AsScraped,
+        }
+    }
+
+    /// Template with the chosen artist format
+    fn template(&self) -> Option<OutputTemplate> {
+        self.output_template
+            .clone()
+            .map(|template| template.artists(self.artists()))
+    }
+
+    fn csv_format(&self) -> anyhow::Result<CsvFormat> {
+        if !self.csv_delimiter.is_ascii() {
+            bail!("--csv-delimiter must be a single ASCII character");
+        }
+        Ok(CsvFormat {
+            delimiter: self.csv_delimiter as u8,
+            always_quote: self.csv_always_quote,
+            bom: self.csv_bom,
+        })
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct ConvertArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+
     /// Target platform to convert the playlist links (e.g., youtube, spotify, apple)
     #[arg(short, long)]
-    dist: Option<Platform>,
+    dist: Platform,
 
-    /// Flag to open change browser headless mode
+    /// Reorder the tracks before converting, e.g. by the playlist's own positions
+    #[arg(long, value_enum)]
+    sort: Option<SortKey>,
+
+    /// Order of the tracks in the output and reports, grouping the matched or unmatched ones
+    /// first keeps their relative order
+    #[arg(long, value_enum, default_value_t = OutputOrder::Input)]
+    output_order: OutputOrder,
+
+    /// Convert only the first N tracks, the saved playlist keeps every track
+    #[arg(long, conflicts_with = "tail")]
+    head: Option<usize>,
+
+    /// Convert only the last N tracks, the saved playlist keeps every track
+    #[arg(long)]
+    tail: Option<usize>,
+
+    /// Convert a random fraction (0 to 1) of the tracks and estimate the match rate of the
+    /// whole playlist, to try matching options before a long conversion
+    #[arg(long, value_name = "RATE")]
+    sample_rate: Option<f64>,
+
+    /// Seed of the --sample-rate selection, the same seed samples the same tracks; a random
+    /// one is printed otherwise
+    #[arg(long, requires = "sample_rate")]
+    seed: Option<u64>,
+
+    #[command(flatten)]
+    matching: MatchArgs,
+
+    /// JSON file of tracks converted in earlier runs or a --save-mapping file, only the others
+    /// are searched and the new links are added to it; tracks with an ISRC match by it before
+    /// their name and artist
+    #[arg(long)]
+    existing: Option<PathBuf>,
+
+    /// Search again the --existing tracks converted more than this many days ago, in case
+    /// their links went dead
+    #[arg(long, value_name = "DAYS", requires = "existing")]
+    cache_ttl: Option<u64>,
+
+    /// Also record every resolved track's link in this JSON mapping of
+    /// `{"Artist - Title": {platform: link}}`, kept across targets and readable as --existing
+    #[arg(long, value_name = "PATH")]
+    save_mapping: Option<PathBuf>,
+
+    /// Retry unmatched tracks with progressively looser scored matching, the last level also
+    /// accepting songs from other albums and years
     #[arg(long, default_value_t = false)]
-    show_browser: bool,
+    retry_unmatched: bool,
+
+    /// Open every converted link and mark the ones no longer leading to a song as unmatched,
+    /// a page load per link
+    #[arg(long, default_value_t = false)]
+    verify_links: bool,
+
+    /// Visit every matched song on the target platform and take its album, duration and year
+    #[arg(long)]
+    enrich: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Strip tracking parameters from the printed links
+    #[arg(long, default_value_t = false)]
+    normalize_output: bool,
+
+    /// Print each link as `source -> target`, naming the track when its source link is unknown
+    #[arg(long, conflicts_with = "output_template")]
+    include_source_link: bool,
+
+    /// Also copy the printed links to the system clipboard, as printed; on Linux a background
+    /// process keeps serving them until something else is copied
+    #[arg(long, default_value_t = false)]
+    clipboard: bool,
+
+    /// Form of the printed Spotify links
+    #[arg(long, value_enum, default_value_t = UriScheme::Https)]
+    uri_scheme: UriScheme,
+
+    /// Print YouTube Music links as regular `www.youtube.com` video links
+    #[arg(long)]
+    youtube_video_links: bool,
+
+    /// Write an HTML page reviewing every track's link and confidence to this file
+    #[arg(long, value_name = "PATH")]
+    report_html: Option<PathBuf>,
+
+    /// Shell command run for every matched track, e.g. a downloader; `{link}` is replaced by the
+    /// quoted link and UNILIST_LINK, UNILIST_NAME, UNILIST_ARTIST, UNILIST_ALBUM and
+    /// UNILIST_CONFIDENCE are set
+    #[arg(long, value_name = "CMD")]
+    exec: Option<String>,
+
+    /// Milliseconds between two --exec runs
+    #[arg(long, requires = "exec", default_value_t = 0)]
+    rate_limit_ms: u64,
+
+    /// Print links opening all the converted tracks at once instead of one link per track, where
+    /// the target has them (Spotify only)
+    #[arg(long, conflicts_with = "output_template")]
+    bundle_link: bool,
+
+    /// Create a Spotify playlist with this name from the converted tracks (needs --spotify-token)
+    #[arg(long)]
+    create_playlist: Option<String>,
+
+    /// Add the converted tracks missing from this existing playlist (Spotify only, needs --spotify-token)
+    #[arg(long, conflicts_with = "create_playlist")]
+    merge_into: Option<String>,
+
+    /// Spotify Web API access token with the playlist-modify-private scope
+    #[arg(long, env = "SPOTIFY_TOKEN", hide_env_values = true)]
+    spotify_token: Option<String>,
+
+    /// Where the tracks are looked up, repeat it to try the next one on the tracks the earlier
+    /// ones missed, e.g. `--resolver spotify-api --resolver browser`
+    #[arg(long = "resolver", value_enum, default_values_t = [Resolver::Browser])]
+    resolvers: Vec<Resolver>,
+
+    /// Print the scored search results for the track at this index of the playlist instead of
+    /// converting
+    #[arg(long)]
+    explain_index: Option<usize>,
+
+    /// Number of candidates printed by --explain-index
+    #[arg(long, default_value_t = 5)]
+    explain_top: usize,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
-struct Track {
-    name: String,
-    artist: String,
-    album: Option<String>,
+/// Shape of the results on stdout
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    /// Links, or the scraped playlist as JSON, once the command finishes
+    Text,
+    /// A JSON object per scraped, converted or unmatched track as it happens, typed by its
+    /// `type` field, and an `error` object if the command fails
+    NdjsonStream,
 }
-///
-/// Enum representing the music platforms
-#[derive(Debug, Clone, PartialEq, ValueEnum)]
-enum Platform {
-    Youtube,
-    Apple,
-    Spotify,
-    Unknown,
+
+/// Shape of the log lines on stderr
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum LogFormat {
+    /// Human readable lines
+    Text,
+    /// One JSON object per line with the event fields, for log pipelines
+    Json,
 }
 
-impl Platform {
-    fn from_url(url: &Url) -> Self {
-        let host = url.host_str().unwrap_or_default();
-        match host {
-            "music.youtube.com" => Platform::Youtube,
-            "music.apple.com" | "itunes.apple.com" => Platform::Apple,
-            "open.spotify.com" | "spotify.com" => Platform::Spotify,
-            _ => Platform::Unknown,
-        }
+/// How the source is read
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum SourceFormat {
+    /// A URL is scraped, `.txt` and `-` list URLs, `.csv` is CSV, anything else JSON
+    Auto,
+    /// Playlist saved with --save
+    Json,
+    /// CSV with name, artist and optional album columns
+    Csv,
+    /// One playlist URL per line
+    Urls,
+    /// One `Artist - Title` query per line, `-` reads them from stdin
+    Queries,
+}
+
+/// Order applied to the tracks with --sort
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum SortKey {
+    /// Index shown on the playlist rows, tracks without one keep their place at the end
+    Position,
+    /// Track name
+    Name,
+    /// Artist line
+    Artist,
+}
+
+/// Order of the tracks in the printed links and reports
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum OutputOrder {
+    /// Order of the converted playlist
+    Input,
+    /// Matched tracks, then the unmatched ones
+    MatchedFirst,
+    /// Unmatched tracks, then the matched ones
+    UnmatchedFirst,
+}
+
+/// Lookup of the tracks on the target platform
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Resolver {
+    /// Search the target platform's site in the browser
+    Browser,
+    /// Search the Spotify Web API, needs --dist spotify and --spotify-token
+    SpotifyApi,
+}
+
+/// Form of the printed Spotify links
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum UriScheme {
+    /// `https://open.spotify.com/track/ID`
+    Https,
+    /// `spotify:track:ID`
+    Uri,
+}
+
+/// Public playlists the self-test scrapes, editorial ones that stay up and keep their length
+const SELF_TEST_PLAYLISTS: &[(Platform, &str)] = &[
+    (
+        Platform::Spotify,
+        "https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M",
+    ),
+    (
+        Platform::Apple,
+        "https://music.apple.com/us/playlist/todays-hits/pl.f4d106fed2bd41149aaacabb233eb5eb",
+    ),
+    (
+        Platform::Youtube,
+        "https://music.youtube.com/playlist?list=RDCLAK5uy_kmPRjHDECIcuVwnKsx2Ng7fyNgFKWNJFs",
+    ),
+];
+
+/// Fewest tracks a self-test scrape must find to pass
+const SELF_TEST_MIN_TRACKS: usize = 10;
+
+/// Some tracks weren't found on the target platform
+const EXIT_UNMATCHED: u8 = 2;
+/// The playlist couldn't be scraped or loaded
+const EXIT_SCRAPE_FAILED: u8 = 3;
+/// The source or target platform isn't supported
+const EXIT_UNSUPPORTED: u8 = 4;
+/// Reading or writing a file failed
+const EXIT_IO: u8 = 5;
+/// No browser could be launched
+const EXIT_NO_BROWSER: u8 = 6;
+
+/// Context marking errors of the scraping stage
+#[derive(Debug)]
+struct ScrapeFailed;
+
+impl fmt::Display for ScrapeFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to scrape playlist")
     }
 }
 
-fn main() {
-    env_logger::init();
+/// Set on the background copy of the CLI serving the clipboard on Linux
+#[cfg(target_os = "linux")]
+const CLIPBOARD_DAEMON_ENV: &str = "UNILIST_CLIPBOARD_DAEMON";
+
+fn main() -> ExitCode {
+    #[cfg(target_os = "linux")]
+    if std::env::var_os(CLIPBOARD_DAEMON_ENV).is_some() {
+        return serve_clipboard();
+    }
     let args = Args::parse();
+    init_logger(args.browser.log_format);
+    let streaming = args.browser.format == OutputFormat::NdjsonStream;
 
-    let options = LaunchOptions::default_builder()
-        .headless(!args.show_browser)
-        .idle_browser_timeout(Duration::from_secs(1000000))
-        .build()
-        .unwrap();
-    let browser = Browser::new(options).unwrap();
-
-    // Parse playlist
-    let playlist = match Url::try_from(args.source.as_str()) {
-        Ok(url) => match Platform::from_url(&url) {
-            Platform::Youtube => fetch_yt_playlist(&browser, &url),
-            Platform::Apple => todo!(),
-            Platform::Spotify => fetch_spotify_playlist(&browser, &url),
-            Platform::Unknown => todo!(),
-        },
-        Err(_) => todo!(),
-    }
-    .expect("Failed to scrape playlist");
-
-    // Save playlist if needed
-    if let Some(path) = args.save {
-        let playlist = serde_json::to_string(&playlist).unwrap();
-        fs::write(path, playlist).unwrap();
-    }
-
-    // Convert to another platform links
-    let links = match args.dist {
-        Some(platform) => match platform {
-            Platform::Youtube => todo!(),
-            Platform::Apple => find_apple_links(&browser, playlist.as_slice()),
-            Platform::Spotify => todo!(),
-            Platform::Unknown => todo!(),
-        },
-        None => Ok(vec![]),
-    }
-    .expect("Failed to convert playlist");
-
-    for link in links {
-        println!("{link}")
-    }
-}
-
-fn fetch_yt_playlist(
-    browser: &Browser,
-    yt_playlist_url: impl AsRef<str>,
-) -> anyhow::Result<Vec<Track>> {
-    let tab = browser.new_tab()?;
-    tab.navigate_to(yt_playlist_url.as_ref())?;
-    let tracks = tab
-        .wait_for_elements("ytmusic-responsive-list-item-renderer")?
-        .into_iter()
-        .filter_map(|el| el.find_elements("yt-formatted-string").ok())
-        .map(|strings| {
-            strings
-                .into_iter()
-                .filter_map(|el| el.get_inner_text().ok())
-                .collect::<Vec<_>>()
-        })
-        .map(|track_info| match track_info.as_slice() {
-            [name, artist, album, _duration, _empty] => Track {
-                name: name.to_string(),
-                artist: artist.to_string(),
-                album: Some(album.to_string()).filter(|s| !s.is_empty()),
-            },
-            _ => unreachable!(),
-        })
+    match run(args) {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(unmatched) => {
+            log::warn!("{unmatched} tracks weren't found");
+            ExitCode::from(EXIT_UNMATCHED)
+        }
+        Err(e) => {
+            if streaming {
+                Event::Error {
+                    message: format!("{e:#}"),
+                }
+                .emit();
+            }
+            eprintln!("Error: {e:#}");
+            if let Some(PlaylistError::Private) = e.downcast_ref() {
+                eprintln!(
+                    "Make the playlist public, or sign in with --show-browser and --user-data-dir"
+                );
+            }
+            if e.chain().any(|e| e.is::<BrowserNotFound>()) {
+                eprintln!("Install Chrome or Chromium, pass --chrome-path, or --fetch-browser to download one");
+            }
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+/// Logs as text, or as one JSON object per line carrying the event fields
+fn init_logger(format: LogFormat) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let mut line = Map::new();
+            line.insert("timestamp".into(), buf.timestamp().to_string().into());
+            line.insert("level".into(), record.level().as_str().into());
+            line.insert("target".into(), record.target().into());
+            line.insert("message".into(), record.args().to_string().into());
+            if let Err(e) = record.key_values().visit(&mut JsonFields(&mut line)) {
+                line.insert("fields_error".into(), e.to_string().into());
+            }
+            writeln!(buf, "{}", Value::Object(line))
+        });
+    }
+    builder.init();
+}
+
+/// Copies log event fields into a JSON object, keeping numbers and booleans typed
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(n) = value.to_u64() {
+            n.into()
+        } else if let Some(n) = value.to_f64() {
+            n.into()
+        } else if let Some(b) = value.to_bool() {
+            b.into()
+        } else {
+            value.to_string().into()
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+fn exit_code(error: &anyhow::Error) -> u8 {
+    if error.chain().any(|e| e.is::<BrowserNotFound>()) {
+        EXIT_NO_BROWSER
+    } else if error.chain().any(|e| e.is::<UnsupportedPlatform>()) {
+        EXIT_UNSUPPORTED
+    } else if error.chain().any(|e| e.is::<io::Error>()) {
+        EXIT_IO
+    } else if error.downcast_ref::<ScrapeFailed>().is_some() {
+        EXIT_SCRAPE_FAILED
+    } else {
+        1
+    }
+}
+
+/// Runs the command, returning the number of tracks that weren't found
+fn run(args: Args) -> anyhow::Result<usize> {
+    match args.command {
+        Command::ExplainUrl { url } => {
+            explain_url(&url)?;
+            Ok(0)
+        }
+        Command::SelfTest { platforms } => {
+            let converter = launch(&args.browser, None, None)?;
+            self_test(&converter, &platforms)?;
+            Ok(0)
+        }
+        Command::DryValidate {
+            source,
+            source_format,
+        } => {
+            let playlist = load_file(&source, source_format)?;
+            let issues = validate(&playlist.tracks);
+            for (idx, issue) in &issues {
+                let track = &playlist.tracks[*idx];
+                println!("{}: {issue} ({} — {})", idx + 1, track.name, track.artist);
+            }
+            if !issues.is_empty() {
+                bail!(
+                    "{} issues in {} tracks of {source}",
+                    issues.len(),
+                    playlist.tracks.len()
+                );
+            }
+            println!("{} tracks, no issues", playlist.tracks.len());
+            Ok(0)
+        }
+        Command::Explain {
+            query,
+            dist,
+            top,
+            matching,
+        } => {
+            let track = parse_query(&query)
+                .ok_or_else(|| anyhow!("Expected the query as \"Song - Artist\""))?;
+            let converter = launch(&args.browser, None, Some(&matching))?;
+            check_layouts(&args.browser, &converter, [dist]);
+            explain(&converter, &track, &dist, &matching.region, top)?;
+            Ok(0)
+        }
+        Command::Scrape(source) => {
+            let converter = launch(&args.browser, Some(&source), None)?;
+            check_layouts(&args.browser, &converter, [url_platform(&source.source)]);
+            let playlist = load_source(&converter, &source)?;
+            if source.save.is_none() && args.browser.format == OutputFormat::Text {
+                println!("{}", playlist.to_json()?);
+            }
+            Ok(0)
+        }
+        Command::Diff {
+            source,
+            target,
+            output,
+        } => {
+            let converter = launch(&args.browser, Some(&source), None)?;
+            let platforms = [url_platform(&source.source), url_platform(&target)];
+            check_layouts(&args.browser, &converter, platforms);
+            let playlist = load_source(&converter, &source)?;
+            compare_with(&converter, &playlist, &target, &output, source.max_tracks)
+        }
+        Command::Convert(convert) => run_convert(&args.browser, *convert),
+    }
+}
+
+/// Launches the browser with the options of the running command
+fn launch(
+    browser: &BrowserArgs,
+    source: Option<&SourceArgs>,
+    matching: Option<&MatchArgs>,
+) -> anyhow::Result<Converter> {
+    let mut builder = Converter::builder()
+        .headless(!browser.show_browser)
+        .fetch_browser(browser.fetch_browser)
+        .stealth(browser.stealth)
+        .warmup(browser.warmup)
+        .event_stream(browser.format == OutputFormat::NdjsonStream)
+        .progress(browser.progress.unwrap_or_else(|| {
+            if io::stdout().is_terminal() {
+                Progress::Bar
+            } else {
+                Progress::Plain
+            }
+        }))
+        .wait(browser.wait)
+        .timeout(Duration::from_secs(browser.timeout_secs))
+        .api_rate_limit(browser.api_rate_limit);
+    if let Some(proxy) = &browser.proxy {
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &browser.chrome_path {
+        builder = builder.chrome_path(path);
+    }
+    if let Some(dir) = &browser.user_data_dir {
+        builder = builder.user_data_dir(dir);
+    }
+    for arg in &browser.chrome_arg {
+        builder = builder.chrome_arg(arg);
+    }
+    if let Some(path) = &browser.selectors {
+        let selectors = Selectors::load(path)
+            .with_context(|| format!("Failed to load selectors from {}", path.display()))?;
+        builder = builder.selectors(selectors);
+    }
+    if let Some(dir) = &browser.screenshot_on_error {
+        builder = builder.screenshot_on_error(dir);
+    }
+
+    if let Some(source) = source {
+        builder = builder
+            .artist_from_album_header(source.artist_from_album_header)
+            .cover_art(source.cover_art)
+            .strict_platform(source.strict_platform)
+            .stream_tracks(source.stream_tracks)
+            .keep_duplicates(source.keep_duplicates)
+            .scroll_stall_threshold(source.scroll_stall_threshold)
+            .scroll_delay(Duration::from_millis(source.scroll_delay_ms));
+        if let Some(max_tracks) = source.max_tracks {
+            builder = builder.max_tracks(max_tracks);
+        }
+        if let Some(iterations) = source.max_scroll_iterations {
+            builder = builder.max_scroll_iterations(iterations);
+        }
+        if let Some(path) = &source.scroll_checkpoint {
+            builder = builder.scroll_checkpoint(path);
+        }
+    }
+
+    if let Some(matching) = matching {
+        builder = builder
+            .concurrency(matching.concurrency)
+            .max_open_tabs(matching.max_open_tabs)
+            .tab_failure_threshold(matching.tab_failure_threshold)
+            .region(&matching.region)
+            .matcher(matching.matcher)
+            .similarity(matching.similarity)
+            .artist_selection(matching.artist_selection)
+            .ambiguous_policy(matching.ambiguous_policy)
+            .album_mode(matching.album_mode)
+            .title_only_fallback(matching.title_only_fallback)
+            .swap_fallback(matching.swap_fallback)
+            .match_year(matching.match_year)
+            .min_confidence(matching.min_confidence)
+            .prefer(matching.prefer)
+            .title_threshold(matching.title_threshold)
+            .artist_threshold(matching.artist_threshold);
+        let mut blocklist = if matching.no_default_artist_blocklist {
+            vec![]
+        } else {
+            DEFAULT_ARTIST_BLOCKLIST
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect()
+        };
+        blocklist.extend(matching.artist_blocklist.iter().cloned());
+        if let Some(path) = &matching.artist_blocklist_file {
+            let content = fs::read_to_string(path).with_context(|| {
+                format!("Failed to read the artist blocklist {}", path.display())
+            })?;
+            blocklist.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        builder = builder
+            .artist_blocklist(blocklist)
+            .record_candidates(matching.match_report_csv.is_some());
+        if let Some(secs) = matching.track_timeout_secs {
+            builder = builder.track_timeout(Duration::from_secs(secs));
+        }
+    }
+
+    let converter = builder.build().context("Failed to launch the browser")?;
+    if browser.show_browser && browser.user_data_dir.is_some() && io::stdin().is_terminal() {
+        eprint!("Press Enter after you've solved any challenge in the browser window...");
+        io::stdin().read_line(&mut String::new())?;
+    }
+    Ok(converter)
+}
+
+/// Platform of a URL source, unknown for files
+fn url_platform(source: &str) -> Platform {
+    Url::parse(source)
+        .map(|url| Platform::from_url(&url))
+        .unwrap_or(Platform::Unknown)
+}
+
+/// Warns about every platform whose layout drifted from the built-in selectors, if asked to
+fn check_layouts(
+    browser: &BrowserArgs,
+    converter: &Converter,
+    platforms: impl IntoIterator<Item = Platform>,
+) {
+    if !browser.scraper_version_check {
+        return;
+    }
+    for platform in platforms.into_iter().collect::<HashSet<_>>() {
+        if let Err(e) = converter.check_layout(&platform) {
+            eprintln!("Warning: {e:#}");
+        }
+    }
+}
+
+/// Warns when the page of `source` reported another track count than was scraped
+///
+/// Reaching the `max_tracks` cap leaves fewer tracks on purpose.
+fn warn_count_mismatch(source: &str, playlist: &Playlist, max_tracks: Option<usize>) {
+    let scraped = playlist.tracks.len();
+    match playlist.reported_total {
+        Some(total) if total != scraped && max_tracks != Some(scraped) => eprintln!(
+            "Warning: {source} reports {total} tracks but {scraped} were scraped, the scroll may have ended early or rows were dropped (repeated songs count once unless --keep-duplicates)"
+        ),
+        _ => {}
+    }
+}
+
+/// Scrapes or reads the source playlist, saving it if asked to
+fn load_source(converter: &Converter, args: &SourceArgs) -> anyhow::Result<Playlist> {
+    let source = &args.source;
+    let format = match args.source_format {
+        SourceFormat::Auto if is_url_list(source) => SourceFormat::Urls,
+        SourceFormat::Auto if source.ends_with(".csv") => SourceFormat::Csv,
+        format => format,
+    };
+    let rescue_partial = |error| save_partial(error, args);
+    let playlist = match (format, Url::try_from(source.as_str())) {
+        (SourceFormat::Auto, Ok(url)) => {
+            let playlist = converter
+                .scrape(&url)
+                .context(ScrapeFailed)
+                .map_err(rescue_partial)?;
+            warn_count_mismatch(source, &playlist, args.max_tracks);
+            playlist
+        }
+        (SourceFormat::Urls, _) => {
+            let urls = read_url_list(source)
+                .with_context(|| format!("Failed to read URLs from {source}"))?;
+            scrape_all(converter, &urls, args).map_err(rescue_partial)?
+        }
+        (format, _) => load_file(source, format)?,
+    };
+
+    if let Some(path) = &args.save {
+        save_playlist(&playlist, path, args.save_compressed)
+            .with_context(|| format!("Failed to save playlist to {}", path.display()))?;
+    }
+    Ok(playlist)
+}
+
+/// Scrapes the self-test playlist of every platform, failing if any scrape finds too few tracks
+fn self_test(converter: &Converter, platforms: &[Platform]) -> anyhow::Result<()> {
+    let mut failed = 0;
+    let tested = SELF_TEST_PLAYLISTS
+        .iter()
+        .filter(|(platform, _)| platforms.is_empty() || platforms.contains(platform))
         .collect::<Vec<_>>();
-    log::info!("Got tracks: {tracks:?}");
-    Ok(tracks)
+    for (platform, url) in &tested {
+        let scraped = Url::parse(url)
+            .map_err(anyhow::Error::from)
+            .and_then(|url| converter.scrape(&url));
+        match scraped {
+            Ok(playlist) if playlist.tracks.len() >= SELF_TEST_MIN_TRACKS => {
+                println!("{platform:?}: pass ({} tracks)", playlist.tracks.len());
+            }
+            Ok(playlist) => {
+                println!(
+                    "{platform:?}: fail (only {} tracks from {url})",
+                    playlist.tracks.len()
+                );
+                failed += 1;
+            }
+            Err(e) => {
+                println!("{platform:?}: fail ({e:#})");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!(
+            "{failed} of {} platforms failed the self-test",
+            tested.len()
+        );
+    }
+    Ok(())
 }
 
-fn fetch_spotify_playlist(
-    browser: &Browser,
-    playlist_url: impl AsRef<str>,
-) -> anyhow::Result<Vec<Track>> {
-    log::info!("Starting scraping spotify playlist");
-    let tab = browser.new_tab()?;
-    tab.navigate_to(playlist_url.as_ref())?;
-    tab.wait_until_navigated()?;
+/// Runs the --exec command for a matched track, failing if it exits unsuccessfully
+fn run_exec(command: &str, track: &Track, found: &Match) -> anyhow::Result<()> {
+    let command = command.replace("{link}", &shell_quote(&found.link));
+    let mut shell = if cfg!(windows) {
+        let mut shell = process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(&command)
+        .env("UNILIST_LINK", &found.link)
+        .env("UNILIST_NAME", &track.name)
+        .env("UNILIST_ARTIST", &track.artist)
+        .env("UNILIST_ALBUM", track.album.as_deref().unwrap_or_default())
+        .env("UNILIST_CONFIDENCE", format!("{:.3}", found.confidence))
+        .status()
+        .with_context(|| format!("Failed to run {command}"))?;
+    if !status.success() {
+        bail!("{command} exited with {status}");
+    }
+    Ok(())
+}
 
-    let mut tracks = vec![];
+/// Quotes text as a single shell word
+fn shell_quote(text: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
 
-    loop {
-        let buf = tab
-            .wait_for_elements(r#"div[data-testid="playlist-tracklist"]>div>div>div:has(a[data-testid="internal-track-link"] > div)"#)
-            .map(|els| els
-            .into_iter()
-            .skip(tracks.len())
-            .filter_map(|el| {
-                if let Err(e) = el.scroll_into_view() {
-                    log::warn!("Failed to scroll to element: {e:?}");
-                }
-                let name = el.find_element("a>div").and_then(|el| el.get_inner_text());
-                let artist = el
-                    .find_element("span>div")
-                    .and_then(|el| el.get_inner_text());
-
-                log::info!("Name: {name:?}, artist: {artist:?}");
-                match (name, artist) {
-                    (Ok(name), Ok(artist)) => Some(Track {
-                        name,
-                        artist,
-                        album: None,
-                    }),
-                    _ => {
-                        log::warn!("Failed to parse track");
-                        None
+/// Reads a playlist from a file in a format needing no browser
+fn load_file(source: &str, format: SourceFormat) -> anyhow::Result<Playlist> {
+    match format {
+        SourceFormat::Urls => bail!("{source} lists URLs, which need scraping"),
+        SourceFormat::Queries => {
+            let content = read_source(source)
+                .with_context(|| format!("Failed to read queries from {source}"))?;
+            Ok(Playlist::from_queries(&content))
+        }
+        SourceFormat::Csv => {
+            Playlist::load_csv(source).with_context(|| format!("Failed to load {source}"))
+        }
+        SourceFormat::Auto if source.ends_with(".csv") => load_file(source, SourceFormat::Csv),
+        SourceFormat::Auto | SourceFormat::Json => {
+            Playlist::load(source).with_context(|| format!("Failed to load {source}"))
+        }
+    }
+}
+
+/// Runs the `convert` command, returning the number of tracks that weren't found
+fn run_convert(browser: &BrowserArgs, args: ConvertArgs) -> anyhow::Result<usize> {
+    if args.create_playlist.is_some() && args.dist != Platform::Spotify {
+        bail!("--create-playlist only supports --dist spotify");
+    }
+    if args.uri_scheme == UriScheme::Uri && args.dist != Platform::Spotify {
+        bail!("--uri-scheme uri only supports --dist spotify");
+    }
+    if args.matching.match_report_csv.is_some() && args.matching.matcher != Matcher::Scored {
+        bail!("--match-report-csv needs --matcher scored");
+    }
+    let csv_format = args.output.csv_format()?;
+    let artists = args.output.artists();
+    if args
+        .sample_rate
+        .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+    {
+        bail!("--sample-rate must be between 0 and 1");
+    }
+    if args.resolvers.contains(&Resolver::SpotifyApi) && args.dist != Platform::Spotify {
+        bail!("--resolver spotify-api only supports --dist spotify");
+    }
+    if args.youtube_video_links && args.dist != Platform::Youtube {
+        bail!("--youtube-video-links only supports --dist youtube");
+    }
+    if let Some(playlist_url) = &args.merge_into {
+        match url_platform(playlist_url) {
+            Platform::Spotify if args.dist == Platform::Spotify => {}
+            Platform::Spotify => bail!("--merge-into a Spotify playlist needs --dist spotify"),
+            Platform::Apple => bail!(
+                "Apple Music's API needs a developer token, --merge-into supports Spotify only"
+            ),
+            _ => bail!("--merge-into supports Spotify playlists only"),
+        }
+    }
+
+    let streaming = browser.format == OutputFormat::NdjsonStream;
+    let mut converter = launch(browser, Some(&args.source), Some(&args.matching))?;
+    for resolver in &args.resolvers {
+        converter = match resolver {
+            Resolver::Browser => converter.with_browser_search(),
+            Resolver::SpotifyApi => {
+                let token = args
+                    .spotify_token
+                    .as_deref()
+                    .context("--resolver spotify-api needs --spotify-token")?;
+                let http = converter.http().clone();
+                converter.with_resolver(SpotifyApiResolver::new(http, token))
+            }
+        };
+    }
+    check_layouts(
+        browser,
+        &converter,
+        [url_platform(&args.source.source), args.dist],
+    );
+    let mut playlist = load_source(&converter, &args.source)?;
+    let platform = args.dist;
+
+    match args.sort {
+        Some(SortKey::Position) => playlist
+            .tracks
+            .sort_by_key(|track| (track.position.is_none(), track.position)),
+        Some(SortKey::Name) => playlist.tracks.sort_by_key(|track| track.key().name),
+        Some(SortKey::Artist) => playlist.tracks.sort_by_key(|track| track.key().artist),
+        None => {}
+    }
+
+    if let Some(head) = args.head {
+        playlist.tracks.truncate(head);
+    }
+    if let Some(tail) = args.tail {
+        let skipped = playlist.tracks.len().saturating_sub(tail);
+        playlist.tracks.drain(..skipped);
+    }
+    let sampled_from = args.sample_rate.map(|rate| {
+        let total = playlist.tracks.len();
+        let seed = args
+            .seed
+            .unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        let mut rng = SplitMix64(seed);
+        playlist.tracks.retain(|_| rng.next_f64() < rate);
+        eprintln!(
+            "Sampled {} of {total} tracks with --seed {seed}",
+            playlist.tracks.len()
+        );
+        total
+    });
+
+    if let Some(idx) = args.explain_index {
+        let track = playlist
+            .tracks
+            .get(idx)
+            .ok_or_else(|| anyhow!("Explained index is out of the playlist"))?;
+        explain(
+            &converter,
+            track,
+            &platform,
+            &args.matching.region,
+            args.explain_top,
+        )?;
+        return Ok(0);
+    }
+
+    let template = args.output.template();
+
+    let mut existing = args
+        .existing
+        .as_ref()
+        .map(|path| {
+            ConvertedLinks::load(path, platform)
+                .with_context(|| format!("Failed to load converted tracks from {}", path.display()))
+        })
+        .transpose()?;
+    if let (Some(days), Some(existing)) = (args.cache_ttl, &mut existing) {
+        let expired = existing.expire(Duration::from_secs(days * 24 * 60 * 60));
+        if expired > 0 {
+            log::info!("Searching again {expired} tracks converted over {days} days ago");
+        }
+    }
+
+    // Convert to another platform links, a playlist already on it keeps its own
+    let passthrough = platform == playlist.source_platform;
+    let mut matches = if passthrough {
+        eprintln!(
+            "Warning: The playlist already comes from {platform:?}, printing its own links without converting"
+        );
+        let matches = source_links(&playlist.tracks, &platform);
+        if streaming {
+            for (track, found) in playlist.tracks.iter().zip(&matches) {
+                match found {
+                    Some(found) => Event::Converted {
+                        track,
+                        link: &found.link,
+                        confidence: found.confidence,
+                    },
+                    None => Event::Unmatched {
+                        track,
+                        reason: "The scraped row had no link".to_string(),
                     },
                 }
-            })
-            .collect::<Vec<_>>());
+                .emit();
+            }
+        }
+        matches
+    } else {
+        convert_missing(
+            &converter,
+            &playlist.tracks,
+            &platform,
+            &existing,
+            streaming,
+        )
+        .context("Failed to convert playlist")?
+    };
 
-        let mut tracks_added = 0;
+    if args.retry_unmatched && !passthrough {
+        let recovered = converter
+            .retry_unmatched(&playlist.tracks, &platform, &mut matches)
+            .context("Failed to retry unmatched tracks")?;
+        for (idx, level) in recovered {
+            let track = &playlist.tracks[idx];
+            eprintln!(
+                "Matched {} — {} at relaxation level {level}",
+                track.name, track.artist
+            );
+        }
+    }
 
-        match buf {
-            Err(e) => {
-                log::error!("Failed to collect buffer of tracks: {e:?}");
-                continue;
-            }
-            Ok(buf) => {
-                for track in buf {
-                    if tracks.contains(&track) {
-                        continue;
-                    }
-                    tracks.push(track);
-                    tracks_added += 1;
+    if args.matching.ambiguous_policy == AmbiguousPolicy::Interactive && !passthrough {
+        review_ambiguous(
+            &converter,
+            &playlist.tracks,
+            &platform,
+            &mut matches,
+            streaming,
+        )?;
+    }
+
+    if args.verify_links {
+        let dead = converter
+            .verify_links(&mut matches)
+            .context("Failed to verify the converted links")?;
+        for (idx, link) in dead {
+            let track = &playlist.tracks[idx];
+            eprintln!(
+                "Warning: {link} for {} — {} didn't load a song, marked unmatched",
+                track.name, track.artist
+            );
+            if streaming {
+                Event::Unmatched {
+                    track,
+                    reason: format!("{link} didn't load a song"),
                 }
+                .emit();
             }
         }
+    }
+
+    if args.enrich {
+        converter
+            .enrich(&mut playlist.tracks, &matches, &platform)
+            .context("Failed to enrich the matched tracks")?;
+    }
+
+    if args.output_order != OutputOrder::Input {
+        let unmatched_first = args.output_order == OutputOrder::UnmatchedFirst;
+        let mut rows = mem::take(&mut playlist.tracks)
+            .into_iter()
+            .zip(mem::take(&mut matches))
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|(_, found)| found.is_some() == unmatched_first);
+        (playlist.tracks, matches) = rows.into_iter().unzip();
+    }
+
+    if let Some(path) = &args.matching.match_report_csv {
+        let report = match_report_csv(&converter.considered_candidates(), &csv_format, &artists)?;
+        fs::write(path, report)
+            .with_context(|| format!("Failed to write the match report to {}", path.display()))?;
+    }
+    if let Some(path) = &args.report_html {
+        fs::write(path, html_report(&playlist.tracks, &matches, &artists))
+            .with_context(|| format!("Failed to write the report to {}", path.display()))?;
+    }
 
-        log::info!("Added {tracks_added} new tracks");
+    let bundles = args.bundle_link.then(|| {
+        let links = matches
+            .iter()
+            .flatten()
+            .map(|found| found.link.clone())
+            .collect::<Vec<_>>();
+        let name = args
+            .create_playlist
+            .as_deref()
+            .unwrap_or("Converted playlist");
+        let bundles = bundle_links(&platform, name, &links);
+        if bundles.is_none() {
+            log::warn!("{platform:?} has no multi-track links, printing one link per track");
+        }
+        bundles
+    });
+    let bundles = bundles.flatten();
 
-        if tracks_added == 0 {
-            break;
+    let mut mapping = args
+        .save_mapping
+        .as_ref()
+        .map(|path| {
+            Mapping::load(path)
+                .with_context(|| format!("Failed to load the mapping from {}", path.display()))
+        })
+        .transpose()?;
+    let mut unmatched = 0;
+    let mut links = vec![];
+    let mut printed = vec![];
+    let mut exec_runs = 0;
+    for (track, found) in playlist.tracks.iter().zip(matches) {
+        let Some(mut found) = found else {
+            unmatched += 1;
+            continue;
+        };
+        if found.query == SearchQuery::Swapped {
+            eprintln!(
+                "Matched {} — {} with name and artist swapped",
+                track.name, track.artist
+            );
+        }
+        links.push(found.link.clone());
+        if let Some(existing) = &mut existing {
+            existing.insert(track, &found.link);
+        }
+        if let Some(mapping) = &mut mapping {
+            mapping.insert(track, platform, &found.link);
+        }
+        if args.normalize_output {
+            found.link = normalize_link(&found.link);
+        }
+        if args.youtube_video_links {
+            if let Some(id) = Url::parse(&found.link)
+                .ok()
+                .and_then(|url| youtube_video_id(&url))
+            {
+                found.link = youtube_link(&id, false);
+            }
+        }
+        if args.uri_scheme == UriScheme::Uri {
+            if let Some(uri) = spotify_track_uri(&found.link) {
+                found.link = uri;
+            }
+        }
+        let line = match &template {
+            _ if bundles.is_some() || streaming => None,
+            Some(template) => Some(template.render(track, Some(&found))),
+            None if args.include_source_link => match &track.source_url {
+                Some(source) => Some(format!("{source} -> {}", found.link)),
+                None => Some(format!(
+                    "{} — {} -> {}",
+                    track.name,
+                    artists.track(track),
+                    found.link
+                )),
+            },
+            None => Some(found.link.clone()),
+        };
+        if let Some(line) = line {
+            println!("{line}");
+            printed.push(line);
+        }
+        if let Some(command) = &args.exec {
+            if exec_runs > 0 {
+                thread::sleep(Duration::from_millis(args.rate_limit_ms));
+            }
+            exec_runs += 1;
+            if let Err(e) = run_exec(command, track, &found) {
+                eprintln!("Warning: --exec failed for {}: {e:#}", found.link);
+            }
+        }
+    }
+    for bundle in bundles.iter().flatten().filter(|_| !streaming) {
+        println!("{bundle}");
+        printed.push(bundle.clone());
+    }
+    if args.clipboard {
+        // Servers and headless sessions have no clipboard, the links are still on stdout
+        if let Err(e) = copy_to_clipboard(&printed.join("\n")) {
+            eprintln!("Warning: Couldn't copy the links to the clipboard: {e:#}");
         }
     }
 
-    log::info!("Finished with {} tracks", tracks.len());
-    Ok(tracks)
-}
+    if let (Some(path), Some(existing)) = (&args.existing, &existing) {
+        existing
+            .save(path)
+            .with_context(|| format!("Failed to save converted tracks to {}", path.display()))?;
+    }
+    if let (Some(path), Some(mapping)) = (&args.save_mapping, &mapping) {
+        mapping
+            .save(path)
+            .with_context(|| format!("Failed to save the mapping to {}", path.display()))?;
+    }
 
-fn find_apple_links(browser: &Browser, tracks: &[Track]) -> anyhow::Result<Vec<String>> {
-    let mut result = vec![];
+    if let Some(name) = &args.create_playlist {
+        match &args.spotify_token {
+            Some(token) => create_spotify_playlist(&converter, token, name, &links)?,
+            None => log::warn!("--create-playlist needs --spotify-token, printed the links only"),
+        }
+    }
 
-    for (idx, track) in tracks.iter().enumerate() {
-        let tab = browser.new_tab()?;
+    if let Some(playlist_url) = &args.merge_into {
+        let token = args
+            .spotify_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("--merge-into needs --spotify-token"))?;
+        merge_into_spotify_playlist(&converter, token, playlist_url, &links)?;
+    }
 
-        let query = format!("{} - {}", &track.name, &track.artist);
-        let url = format!(
-            "https://music.apple.com/us/search?term={}",
-            urlencoding::encode(&query)
+    if let Some(total) = sampled_from.filter(|_| !playlist.tracks.is_empty()) {
+        let sampled = playlist.tracks.len();
+        let rate = (sampled - unmatched) as f64 / sampled as f64;
+        eprintln!(
+            "Matched {} of {sampled} sampled tracks ({:.1}%), about {:.0} of all {total}",
+            sampled - unmatched,
+            rate * 100.0,
+            rate * total as f64
         );
+    }
+
+    Ok(unmatched)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    arboard::Clipboard::new()?.set_text(text)?;
+    Ok(())
+}
 
-        tab.navigate_to(&url)?;
+/// X11 and Wayland clipboards are served by the process that set them and empty once it exits,
+/// so a background copy of the CLI holds the text until something else is copied
+#[cfg(target_os = "linux")]
+fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    // Fails here without a display, the background copy couldn't report it
+    arboard::Clipboard::new()?;
+    let mut daemon = process::Command::new(std::env::current_exe()?)
+        .env(CLIPBOARD_DAEMON_ENV, "1")
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .spawn()
+        .context("Failed to start the clipboard process")?;
+    daemon
+        .stdin
+        .take()
+        .expect("Stdin is piped")
+        .write_all(text.as_bytes())?;
+    Ok(())
+}
 
-        if let Ok(url) = try_find_apple_song_link(&tab, track) {
-            log::info!("[{}/{}] Song: {:#?}", idx + 1, tracks.len(), url);
-            result.push(url)
+/// Sets the clipboard to stdin and serves it until it's replaced, see [`copy_to_clipboard`]
+#[cfg(target_os = "linux")]
+fn serve_clipboard() -> ExitCode {
+    use arboard::SetExtLinux;
+
+    let served = io::read_to_string(io::stdin())
+        .map_err(anyhow::Error::from)
+        .and_then(|text| Ok(arboard::Clipboard::new()?.set().wait().text(text)?));
+    match served {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
+}
+
+/// Small seedable generator for reproducible samples, see <https://prng.di.unimi.it/splitmix64.c>
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Uniform in `0..1`
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Prints every source track flagged `matched` or `missing` from the target playlist,
+/// returning the number of missing ones
+fn compare_with(
+    converter: &Converter,
+    playlist: &Playlist,
+    target: &str,
+    output: &OutputArgs,
+    max_tracks: Option<usize>,
+) -> anyhow::Result<usize> {
+    let template = output.template();
+    let artists = output.artists();
+    let url = Url::parse(target).with_context(|| format!("{target} isn't a playlist URL"))?;
+    let existing = converter.scrape(&url).context(ScrapeFailed)?;
+    warn_count_mismatch(target, &existing, max_tracks);
+    let present = existing
+        .tracks
+        .iter()
+        .map(Track::key)
+        .collect::<HashSet<_>>();
+
+    let mut missing = 0;
+    for track in &playlist.tracks {
+        let flag = if present.contains(&track.key()) {
+            "matched"
         } else {
-            log::warn!(
-                "[{}/{}] Url not found for {}",
-                idx + 1,
-                tracks.len(),
-                track.name
-            );
+            missing += 1;
+            "missing"
+        };
+        match &template {
+            Some(template) => println!("{flag}\t{}", template.render(track, None)),
+            None => println!("{flag}\t{} — {}", track.name, artists.track(track)),
+        }
+    }
+    eprintln!(
+        "{} of {} tracks are already on the target playlist",
+        playlist.tracks.len() - missing,
+        playlist.tracks.len()
+    );
+    Ok(missing)
+}
+
+fn create_spotify_playlist(
+    converter: &Converter,
+    token: &str,
+    name: &str,
+    links: &[String],
+) -> anyhow::Result<()> {
+    let api = SpotifyApi::new(converter.http(), token);
+    let uris = links
+        .iter()
+        .filter_map(|link| spotify_track_uri(link))
+        .collect::<Vec<_>>();
+
+    let playlist = api
+        .create_playlist(name)
+        .context("Failed to create Spotify playlist")?;
+    api.add_tracks(&playlist.id, &uris)
+        .context("Failed to add tracks to the Spotify playlist")?;
+
+    log::info!("Added {} tracks to {name}", uris.len());
+    println!("{}", playlist.url);
+    Ok(())
+}
+
+fn merge_into_spotify_playlist(
+    converter: &Converter,
+    token: &str,
+    playlist_url: &str,
+    links: &[String],
+) -> anyhow::Result<()> {
+    let playlist_id = spotify_id(playlist_url, "playlist")
+        .ok_or_else(|| anyhow!("{playlist_url} isn't a Spotify playlist link"))?;
+    let api = SpotifyApi::new(converter.http(), token);
+
+    let mut present = api
+        .playlist_track_uris(&playlist_id)
+        .context("Failed to read the target playlist")?;
+    let (new, skipped): (Vec<_>, Vec<_>) = links
+        .iter()
+        .filter_map(|link| spotify_track_uri(link))
+        .partition(|uri| present.insert(uri.clone()));
+
+    api.add_tracks(&playlist_id, &new)
+        .context("Failed to add tracks to the target playlist")?;
+    eprintln!(
+        "Added {} tracks, skipped {} already present",
+        new.len(),
+        skipped.len()
+    );
+    Ok(())
+}
+
+/// Sources listing one playlist URL per line, `-` reads them from stdin
+fn is_url_list(source: &str) -> bool {
+    source == "-" || source.ends_with(".txt")
+}
+
+/// Reads a file, or stdin for `-`
+fn read_source(source: &str) -> io::Result<String> {
+    if source == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(source)
+    }
+}
+
+fn read_url_list(source: &str) -> io::Result<Vec<String>> {
+    Ok(read_source(source)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Scrapes every URL into one playlist, optionally skipping unsupported ones and those outside
+/// --only-platforms
+fn scrape_all(
+    converter: &Converter,
+    urls: &[String],
+    args: &SourceArgs,
+) -> anyhow::Result<Playlist> {
+    let mut platforms = HashSet::new();
+    let mut tracks = vec![];
+    // Known only while every scraped page reported its count
+    let mut reported_total = Some(0);
+    let mut skipped = 0;
+    let mut left_out = 0;
+    let allowed = |platform: Platform| {
+        args.only_platforms.is_empty() || args.only_platforms.contains(&platform)
+    };
+
+    for source in urls {
+        // Share links only tell their platform once followed, so they're checked after scraping
+        let parsed = Url::parse(source).ok();
+        let is_video = parsed
+            .as_ref()
+            .is_some_and(|url| youtube_video_id(url).is_some());
+        let detected = match &parsed {
+            Some(_) if is_video => Platform::Youtube,
+            Some(url) => Platform::from_url(url),
+            None => Platform::Unknown,
+        };
+        if detected != Platform::Unknown && !allowed(detected) {
+            log::info!("Leaving out {source}, {detected:?} isn't in --only-platforms");
+            left_out += 1;
+            continue;
         }
 
-        if let Err(e) = tab.close(true) {
-            log::error!("Failed to close tab with {e:?}")
+        let url = match parsed.filter(|_| is_video) {
+            // A single video is read from its page, regular YouTube links included
+            Some(video) => Ok(video),
+            None => Url::parse(source)
+                .map_err(anyhow::Error::from)
+                .and_then(|url| match Platform::from_url(&url) {
+                    Platform::Unknown if args.strict_platform || !is_short_link(&url) => {
+                        Err(UnsupportedPlatform::Source(Platform::Unknown).into())
+                    }
+                    _ => Ok(url),
+                }),
+        };
+        let playlist = url.and_then(|url| converter.scrape(&url));
+
+        match playlist {
+            Ok(playlist) if !allowed(playlist.source_platform) => {
+                log::info!(
+                    "Leaving out {source}, {:?} isn't in --only-platforms",
+                    playlist.source_platform
+                );
+                left_out += 1;
+            }
+            Ok(playlist) => {
+                platforms.insert(playlist.source_platform);
+                warn_count_mismatch(source, &playlist, args.max_tracks);
+                reported_total = reported_total
+                    .zip(playlist.reported_total)
+                    .map(|(total, reported)| total + reported);
+                tracks.extend(playlist.tracks);
+            }
+            Err(e)
+                if args.skip_unsupported
+                    && (e.is::<UnsupportedPlatform>() || e.is::<ParseError>()) =>
+            {
+                log::warn!("Skipping {source}: {e}");
+                skipped += 1;
+            }
+            Err(e) => {
+                if let Some(PartialScrape(partial)) = e.downcast_ref() {
+                    platforms.insert(partial.source_platform);
+                    tracks.extend(partial.tracks.iter().cloned());
+                }
+                let partial = PartialScrape(Playlist {
+                    source_platform: merged_platform(platforms),
+                    tracks,
+                    reported_total: None,
+                });
+                return Err(e.context(partial).context(ScrapeFailed));
+            }
         }
     }
 
-    Ok(result)
+    if skipped > 0 {
+        log::warn!("Skipped {skipped} of {} sources", urls.len());
+    }
+    if left_out > 0 {
+        log::info!(
+            "Left out {left_out} of {} sources outside --only-platforms",
+            urls.len()
+        );
+    }
+
+    Ok(Playlist {
+        source_platform: merged_platform(platforms),
+        tracks,
+        reported_total,
+    })
 }
 
-fn try_find_apple_song_link(tab: &Tab, track: &Track) -> anyhow::Result<String> {
-    tab.wait_for_element(r#"div[aria-label="Songs"]"#)?
-        .wait_for_elements("li")?
-        .into_iter()
-        .filter_map(|el| el.find_element("a").ok())
-        .filter(|el| {
-            matches!(
-                el.get_inner_text()
-                    .map(|title| title.to_lowercase() == track.name.to_lowercase()),
-                Ok(true)
-            )
-        })
-        .filter_map(|el| el.get_attribute_value("href").ok())
-        .next()
-        .flatten()
-        .and_then(|href| {
-            urlencoding::decode(&href)
-                .ok()
-                .map(|href| href.into_owned())
+/// Links the tracks were scraped with, for targets equal to the source
+///
+/// Tracks whose rows had no link count as unmatched rather than being searched for.
+fn source_links(tracks: &[Track], target: &Platform) -> Vec<Option<Match>> {
+    tracks
+        .iter()
+        .map(|track| {
+            let source = track.source_url.as_deref()?;
+            Some(Match {
+                link: same_provider_link(source, target).unwrap_or_else(|| source.to_string()),
+                confidence: 1.0,
+                query: SearchQuery::default(),
+            })
         })
-        .ok_or_else(|| anyhow::anyhow!("Song not found"))
+        .collect()
 }
 
-#[allow(dead_code)] // Could be useful in the impl of the other music sources
-fn get_body_scroll_height(tab: &Tab) -> anyhow::Result<u64> {
-    tab.evaluate("document.body.scrollHeight", true)
-        .ok()
-        .and_then(|obj| match obj.value {
-            Some(serde_json::Value::Number(height)) => height.as_u64(),
-            unknown => panic!("Unknown height type: {unknown:?}"),
+/// Converts the tracks missing from `existing`, reusing its links for the others
+///
+/// Reused links are streamed as converted events, the converter streams the searched ones.
+fn convert_missing(
+    converter: &Converter,
+    tracks: &[Track],
+    platform: &Platform,
+    existing: &Option<ConvertedLinks>,
+    streaming: bool,
+) -> anyhow::Result<Vec<Option<Match>>> {
+    let Some(existing) = existing else {
+        return converter.convert(tracks, platform);
+    };
+    let missing = tracks
+        .iter()
+        .filter(|track| existing.get(track).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    log::info!(
+        "{} of {} tracks are already converted",
+        tracks.len() - missing.len(),
+        tracks.len()
+    );
+
+    let mut found = converter.convert(&missing, platform)?.into_iter();
+    Ok(tracks
+        .iter()
+        .map(|track| match existing.get(track) {
+            // Links curated in the file are trusted as they are
+            Some(link) => {
+                if streaming {
+                    Event::Converted {
+                        track,
+                        link,
+                        confidence: 1.0,
+                    }
+                    .emit();
+                }
+                Some(Match {
+                    link: link.to_string(),
+                    confidence: 1.0,
+                    query: SearchQuery::default(),
+                })
+            }
+            None => found.next().flatten(),
         })
-        .ok_or(anyhow!("Failed to get height"))
+        .collect())
+}
+
+/// Search results shown per ambiguous track under `--ambiguous-policy interactive`
+const REVIEW_CANDIDATES: usize = 5;
+
+/// Asks which search result the unmatched tracks with an ambiguous title should take
+///
+/// Without a terminal on stdin they're listed and stay unmatched.
+fn review_ambiguous(
+    converter: &Converter,
+    tracks: &[Track],
+    platform: &Platform,
+    matches: &mut [Option<Match>],
+    streaming: bool,
+) -> anyhow::Result<()> {
+    let pending = (0..tracks.len())
+        .filter(|&idx| matches[idx].is_none() && is_ambiguous_title(&tracks[idx].name))
+        .collect::<Vec<_>>();
+    if pending.is_empty() {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        for &idx in &pending {
+            let track = &tracks[idx];
+            eprintln!(
+                "Warning: {} — {} has an ambiguous title, left unmatched for review",
+                track.name, track.artist
+            );
+        }
+        return Ok(());
+    }
+
+    for idx in pending {
+        let track = &tracks[idx];
+        let ranked = match converter.explain(track, platform) {
+            Ok(ranked) => ranked,
+            Err(e) => {
+                eprintln!(
+                    "Warning: Couldn't search {} — {}: {e:#}",
+                    track.name, track.artist
+                );
+                continue;
+            }
+        };
+        if ranked.is_empty() {
+            eprintln!(
+                "No results for {} — {}, left unmatched",
+                track.name, track.artist
+            );
+            continue;
+        }
+        eprintln!(
+            "{} — {} has an ambiguous title, pick a result:",
+            track.name, track.artist
+        );
+        let shown = &ranked[..ranked.len().min(REVIEW_CANDIDATES)];
+        for (number, (candidate, score)) in shown.iter().enumerate() {
+            eprintln!(
+                "  {}) {:.2} {} - {} {}",
+                number + 1,
+                score.confidence(),
+                candidate.title,
+                candidate.artist,
+                candidate.link
+            );
+        }
+        eprint!("Number, or Enter to leave it unmatched: ");
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        let Some((candidate, score)) = answer
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| shown.get(number.checked_sub(1)?))
+        else {
+            continue;
+        };
+        let found = Match {
+            link: candidate.link.clone(),
+            confidence: score.confidence(),
+            query: SearchQuery::TitleAndArtist,
+        };
+        if streaming {
+            Event::Converted {
+                track,
+                link: &found.link,
+                confidence: found.confidence,
+            }
+            .emit();
+        }
+        matches[idx] = Some(found);
+    }
+    Ok(())
+}
+
+/// Platform shared by every scraped source, unknown for mixed ones
+fn merged_platform(platforms: HashSet<Platform>) -> Platform {
+    let mut platforms = platforms.into_iter();
+    match (platforms.next(), platforms.next()) {
+        (Some(platform), None) => platform,
+        _ => Platform::Unknown,
+    }
+}
+
+/// Writes the playlist to --save, compressed if asked to
+fn save_playlist(playlist: &Playlist, path: &Path, compressed: bool) -> anyhow::Result<()> {
+    if compressed {
+        playlist.save_compressed(path)
+    } else {
+        playlist.save(path)
+    }
+}
+
+/// Saves the tracks a failed scrape collected if --allow-partial-save is set, passing the error on
+fn save_partial(error: anyhow::Error, args: &SourceArgs) -> anyhow::Error {
+    let (true, Some(path), Some(PartialScrape(partial))) =
+        (args.allow_partial_save, &args.save, error.downcast_ref())
+    else {
+        return error;
+    };
+    match save_playlist(partial, path, args.save_compressed) {
+        Ok(()) => log::warn!(
+            "Saved {} tracks scraped before the failure to {}",
+            partial.tracks.len(),
+            path.display()
+        ),
+        Err(e) => log::error!(
+            "Failed to save the partial playlist to {}: {e}",
+            path.display()
+        ),
+    }
+    error
+}
+
+/// Splits "Song - Artist" into a track
+fn parse_query(query: &str) -> Option<Track> {
+    let (name, artist) = query.split_once(" - ")?;
+    Some(Track {
+        name: name.trim().to_string(),
+        artist: artist.trim().to_string(),
+        ..Default::default()
+    })
+}
+
+fn explain_url(url: &str) -> anyhow::Result<()> {
+    let url = Url::parse(url).with_context(|| format!("Not a URL: {url}"))?;
+    println!("platform: {:?}", Platform::from_url(&url));
+    if is_short_link(&url) {
+        println!("short link: followed in the browser before scraping");
+    }
+    match link_id(&url) {
+        Some(LinkId { kind, id }) => {
+            println!("kind: {kind}");
+            println!("id: {id}");
+        }
+        None => println!("id: not recognized"),
+    }
+    println!("normalized: {}", normalize_link(url.as_str()));
+    Ok(())
+}
+
+fn explain(
+    converter: &Converter,
+    track: &Track,
+    platform: &Platform,
+    region: &str,
+    top: usize,
+) -> anyhow::Result<()> {
+    let ranked = converter
+        .explain(track, platform)
+        .context("Failed to search the target platform")?;
+
+    println!("{} - {}", track.name, track.artist);
+    println!("searched {}", search_url(platform, track, region)?);
+    if track.duration_secs.is_none() {
+        println!("duration not compared, the query has no length");
+    }
+    for (candidate, score) in ranked.iter().take(top) {
+        let duration = match score.duration {
+            Some(duration) => format!(", duration {duration:.2}"),
+            None => String::new(),
+        };
+        println!(
+            "{:.2} (title {:.2}, artist {:.2}{duration}) {} - {} {}",
+            score.confidence(),
+            score.title,
+            score.artist,
+            candidate.title,
+            candidate.artist,
+            candidate.link
+        );
+    }
+    Ok(())
 }
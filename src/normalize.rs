@@ -0,0 +1,140 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Words marking a bracketed or dashed title suffix as a variant rather than part of the name
+const VARIANT_MARKERS: &[&str] = &[
+    "feat.",
+    "ft.",
+    "with ",
+    "remaster",
+    "radio edit",
+    "single version",
+    "album version",
+    "explicit",
+    "clean",
+];
+
+/// Separators that start the featured artists in an artist line
+const FEATURING_SEPARATORS: &[&str] = &[" feat. ", " ft. ", " featuring "];
+
+/// Separators between the artists of an artist line, featuring ones included
+const ARTIST_SEPARATORS: &[&str] = &[", ", " & ", " x ", " feat. ", " ft. ", " featuring "];
+
+/// Lowercases the title and strips variant suffixes like `(Remastered 2011)` or `- Radio Edit`
+///
+/// Precomposed and decomposed accents normalize, and so score, the same:
+///
+/// ```
+/// use unilist::{normalize_artist, normalize_title, Candidate, Score, Similarity, Track};
+///
+/// let precomposed = "Beyonc\u{e9}";
+/// let decomposed = "Beyonce\u{301}";
+/// assert_eq!(normalize_title(precomposed), normalize_title(decomposed));
+/// assert_eq!(normalize_artist(precomposed), normalize_artist(decomposed));
+///
+/// let track = Track {
+///     name: precomposed.to_string(),
+///     artist: precomposed.to_string(),
+///     ..Default::default()
+/// };
+/// let candidate = Candidate {
+///     title: decomposed.to_string(),
+///     artist: decomposed.to_string(),
+///     link: "https://music.apple.com/us/song/1".to_string(),
+///     track_number: None,
+///     explicit: false,
+///     year: None,
+///     album: None,
+///     duration_secs: None,
+/// };
+/// let score = Score::of(&candidate, &track, Similarity::default());
+/// assert_eq!(score.confidence(), 1.0);
+/// ```
+pub fn normalize_title(title: &str) -> String {
+    let mut title = collapse_whitespace(&nfc(title).to_lowercase());
+
+    loop {
+        let stripped = strip_bracketed_variant(&title)
+            .or_else(|| strip_dashed_variant(&title))
+            .map(str::to_string);
+        match stripped {
+            Some(stripped) => title = stripped,
+            None => return title,
+        }
+    }
+}
+
+/// Lowercases the artist and drops featured artists
+pub fn normalize_artist(artist: &str) -> String {
+    let artist = collapse_whitespace(&nfc(artist).to_lowercase());
+    let primary = FEATURING_SEPARATORS
+        .iter()
+        .filter_map(|separator| artist.split_once(separator))
+        .map(|(primary, _)| primary)
+        .min_by_key(|primary| primary.len())
+        .unwrap_or(&artist);
+    primary.trim().to_string()
+}
+
+/// Composes characters so `e` plus a combining accent equals the precomposed `é`
+pub(crate) fn nfc(s: &str) -> String {
+    s.nfc().collect()
+}
+
+/// Splits an artist line like `A, B & C feat. D` into its artists
+pub fn split_artists(artist: &str) -> Vec<String> {
+    let mut artists = vec![artist.to_string()];
+    for separator in ARTIST_SEPARATORS {
+        artists = artists
+            .iter()
+            .flat_map(|artist| artist.split(separator))
+            .map(|artist| artist.trim().to_string())
+            .filter(|artist| !artist.is_empty())
+            .collect();
+    }
+    artists
+}
+
+/// Seconds of a length like `3:45` or `1:02:03`
+pub(crate) fn parse_clock(text: &str) -> Option<u32> {
+    let parts = text.split(':').collect::<Vec<_>>();
+    let digits = |part: &&str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if !(2..=3).contains(&parts.len())
+        || !parts.iter().all(digits)
+        || parts[1..].iter().any(|part| part.len() != 2)
+    {
+        return None;
+    }
+    parts
+        .iter()
+        .try_fold(0, |secs, part| Some(secs * 60 + part.parse::<u32>().ok()?))
+}
+
+/// Whether the text is a length like `3:45`, which scrapers take for a field when the row
+/// layout shifts
+pub(crate) fn is_duration(text: &str) -> bool {
+    parse_clock(text).is_some()
+}
+
+fn strip_bracketed_variant(title: &str) -> Option<&str> {
+    let (open, close) = match title.chars().last()? {
+        ')' => ('(', ')'),
+        ']' => ('[', ']'),
+        _ => return None,
+    };
+    let start = title.rfind(open)?;
+    let inner = title[start + 1..].trim_end_matches(close);
+    is_variant(inner).then(|| title[..start].trim_end())
+}
+
+fn strip_dashed_variant(title: &str) -> Option<&str> {
+    let (name, suffix) = title.rsplit_once(" - ")?;
+    is_variant(suffix).then(|| name.trim_end())
+}
+
+fn is_variant(suffix: &str) -> bool {
+    VARIANT_MARKERS.iter().any(|marker| suffix.contains(marker))
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,288 @@
+use anyhow::bail;
+
+use crate::{split_artists, ConsideredCandidate, Match, SearchQuery, Track};
+
+/// Placeholders accepted in an [`OutputTemplate`]
+const PLACEHOLDERS: &[&str] = &[
+    "name",
+    "artist",
+    "album",
+    "year",
+    "duration",
+    "cover",
+    "source",
+    "link",
+    "confidence",
+];
+
+/// Per-track line like `"{artist} — {name}: {link}"`, `{{` and `}}` print literal braces
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+    artists: ArtistFormat,
+}
+
+/// How the printed outputs and the `{artist}` placeholder list the artists of a track
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ArtistFormat {
+    /// The artist line as scraped
+    #[default]
+    AsScraped,
+    /// Every artist, joined with the separator
+    Joined(String),
+    /// Only the first artist
+    Primary,
+}
+
+impl ArtistFormat {
+    pub fn apply(&self, artist: &str) -> String {
+        match self {
+            ArtistFormat::AsScraped => artist.to_string(),
+            ArtistFormat::Joined(separator) => split_artists(artist).join(separator),
+            ArtistFormat::Primary => split_artists(artist).into_iter().next().unwrap_or_default(),
+        }
+    }
+
+    /// Artists of a scraped track, split from its own list rather than the joined line
+    pub fn track(&self, track: &Track) -> String {
+        match self {
+            ArtistFormat::AsScraped => track.artist.clone(),
+            ArtistFormat::Joined(separator) => track.artist_names().join(separator),
+            ArtistFormat::Primary => track.artist_names().into_iter().next().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+impl OutputTemplate {
+    /// Parses the template, rejecting unknown placeholders
+    pub fn parse(template: &str) -> anyhow::Result<Self> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => bail!("Unclosed {{ in output template"),
+                        }
+                    }
+                    if !PLACEHOLDERS.contains(&name.as_str()) {
+                        bail!(
+                            "Unknown placeholder {{{name}}}, expected one of {}",
+                            PLACEHOLDERS.join(", ")
+                        );
+                    }
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                    segments.push(Segment::Placeholder(name));
+                }
+                '}' => bail!("Unmatched }} in output template"),
+                c => text.push(c),
+            }
+        }
+        segments.push(Segment::Text(text));
+
+        Ok(Self {
+            segments,
+            artists: ArtistFormat::default(),
+        })
+    }
+
+    /// Lists the artists of `{artist}` in the given format, as scraped by default
+    pub fn artists(mut self, artists: ArtistFormat) -> Self {
+        self.artists = artists;
+        self
+    }
+
+    /// Fills the placeholders, missing values render as empty strings
+    pub fn render(&self, track: &Track, found: Option<&Match>) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Placeholder(name) => match name.as_str() {
+                    "name" => track.name.clone(),
+                    "artist" => self.artists.track(track),
+                    "album" => track.album.clone().unwrap_or_default(),
+                    "year" => track.year.map(|year| year.to_string()).unwrap_or_default(),
+                    "duration" => track
+                        .duration_secs
+                        .map(|secs| format!("{}:{:02}", secs / 60, secs % 60))
+                        .unwrap_or_default(),
+                    "source" => track.source_url.clone().unwrap_or_default(),
+                    "cover" => track.cover_url.clone().unwrap_or_default(),
+                    "link" => found.map(|found| found.link.clone()).unwrap_or_default(),
+                    "confidence" => found
+                        .map(|found| format!("{:.2}", found.confidence))
+                        .unwrap_or_default(),
+                    _ => unreachable!("Placeholders are validated on parse"),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Self-contained HTML page listing every track with its link, confidence and cover art
+///
+/// `matches` pairs with `tracks` by index like the result of [`Converter::convert`](crate::Converter::convert).
+pub fn html_report(tracks: &[Track], matches: &[Option<Match>], artists: &ArtistFormat) -> String {
+    let found = matches.iter().flatten().count();
+    let mut rows = String::new();
+    for (track, found) in tracks.iter().zip(matches) {
+        let cover = track
+            .cover_url
+            .as_deref()
+            .map(|url| {
+                format!(
+                    r#"<img src="{}" alt="" width="48" height="48">"#,
+                    escape(url)
+                )
+            })
+            .unwrap_or_default();
+        let (link, confidence, class) = match found {
+            Some(found) => (
+                format!(r#"<a href="{0}">{0}</a>"#, escape(&found.link)),
+                match found.query {
+                    SearchQuery::TitleAndArtist => format!("{:.2}", found.confidence),
+                    SearchQuery::TitleOnly => format!("{:.2} (title only)", found.confidence),
+                    SearchQuery::Swapped => format!("{:.2} (swapped)", found.confidence),
+                },
+                "matched",
+            ),
+            None => ("not found".to_string(), String::new(), "unmatched"),
+        };
+        rows.push_str(&format!(
+            "<tr class=\"{class}\"><td>{cover}</td><td>{}</td><td>{}</td><td>{}</td><td>{link}</td><td>{confidence}</td></tr>\n",
+            escape(&track.name),
+            escape(&artists.track(track)),
+            escape(track.album.as_deref().unwrap_or_default()),
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Playlist conversion</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+td, th {{ border-bottom: 1px solid #ddd; padding: 0.4em; text-align: left; }}
+tr.unmatched {{ background: #fdecea; }}
+</style>
+</head>
+<body>
+<h1>Playlist conversion</h1>
+<p>{found} of {total} tracks found, {unmatched} unmatched</p>
+<table>
+<tr><th></th><th>Name</th><th>Artist</th><th>Album</th><th>Link</th><th>Confidence</th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        total = tracks.len(),
+        unmatched = tracks.len() - found,
+    )
+}
+
+/// Dialect of written CSV files, spreadsheets in many European locales expect `;`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CsvFormat {
+    pub delimiter: u8,
+    /// Quote every field rather than only those holding a delimiter, quote or newline
+    pub always_quote: bool,
+    /// Start with a UTF-8 byte order mark, without it Excel reads the file in the locale's
+    /// legacy encoding
+    pub bom: bool,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            always_quote: false,
+            bom: false,
+        }
+    }
+}
+
+/// CSV with a row per candidate the scored matcher weighed, see [`ConverterBuilder::record_candidates`](crate::ConverterBuilder::record_candidates)
+pub fn match_report_csv(
+    considered: &[ConsideredCandidate],
+    format: &CsvFormat,
+    artists: &ArtistFormat,
+) -> anyhow::Result<String> {
+    let bom = if format.bom { "\u{feff}" } else { "" };
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(format.delimiter)
+        .quote_style(if format.always_quote {
+            csv::QuoteStyle::Always
+        } else {
+            csv::QuoteStyle::Necessary
+        })
+        .from_writer(bom.as_bytes().to_vec());
+    writer.write_record([
+        "name",
+        "artist",
+        "query",
+        "candidate_title",
+        "candidate_artist",
+        "link",
+        "title_score",
+        "artist_score",
+        "duration_score",
+        "confidence",
+        "chosen",
+    ])?;
+    for row in considered {
+        let query = match row.query {
+            SearchQuery::TitleAndArtist => "title_and_artist",
+            SearchQuery::TitleOnly => "title_only",
+            SearchQuery::Swapped => "swapped",
+        };
+        writer.write_record([
+            row.track.name.as_str(),
+            &artists.track(&row.track),
+            query,
+            row.candidate.title.as_str(),
+            &artists.apply(&row.candidate.artist),
+            row.candidate.link.as_str(),
+            &format!("{:.3}", row.score.title),
+            &format!("{:.3}", row.score.artist),
+            &row.score
+                .duration
+                .map(|duration| format!("{duration:.3}"))
+                .unwrap_or_default(),
+            &format!("{:.3}", row.score.confidence()),
+            if row.chosen { "true" } else { "false" },
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Escapes text for HTML content and quoted attributes
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Enum representing the music platforms
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum, Deserialize, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Youtube,
+    Apple,
+    Spotify,
+    Unknown,
+}
+
+impl Platform {
+    pub fn from_url(url: &Url) -> Self {
+        let host = url.host_str().unwrap_or_default();
+        match host {
+            "music.youtube.com" => Platform::Youtube,
+            "music.apple.com" | "itunes.apple.com" => Platform::Apple,
+            "open.spotify.com" | "spotify.com" => Platform::Spotify,
+            _ => Platform::Unknown,
+        }
+    }
+}
+
+/// Requested scrape or conversion isn't implemented for the platform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnsupportedPlatform {
+    Source(Platform),
+    Target(Platform),
+}
+
+impl fmt::Display for UnsupportedPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Source(platform) => write!(f, "Scraping {platform:?} playlists is not supported"),
+            Self::Target(platform) => write!(f, "Converting to {platform:?} is not supported"),
+        }
+    }
+}
+
+impl std::error::Error for UnsupportedPlatform {}
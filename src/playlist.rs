@@ -0,0 +1,209 @@
+use std::{
+    fmt, fs,
+    io::{Read, Write},
+    path::Path,
+};
+
+use anyhow::bail;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Platform, Track};
+
+/// Scraped playlist as saved to and loaded from JSON
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Playlist {
+    /// Platform the tracks were scraped from
+    pub source_platform: Platform,
+    pub tracks: Vec<Track>,
+    /// Track count the playlist page showed in its header, when it shows one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reported_total: Option<usize>,
+}
+
+/// Error context carrying the tracks a scrape collected before it failed
+#[derive(Debug)]
+pub struct PartialScrape(pub Playlist);
+
+impl fmt::Display for PartialScrape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Scraped {} tracks before failing", self.0.tracks.len())
+    }
+}
+
+/// Playlist page that loaded without any tracks to scrape, an access issue rather than a
+/// broken scraper
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaylistError {
+    /// Only its owner can see it
+    Private,
+    /// Removed, or never existed
+    NotFound,
+    /// Loaded but lists no tracks
+    Empty,
+}
+
+impl fmt::Display for PlaylistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Private => write!(f, "The playlist is private"),
+            Self::NotFound => write!(f, "The playlist doesn't exist or was removed"),
+            Self::Empty => write!(f, "The playlist has no tracks"),
+        }
+    }
+}
+
+impl std::error::Error for PlaylistError {}
+
+/// Version of the saved JSON layout, bumped whenever loading old files needs a migration
+///
+/// - 0: a bare array of tracks
+/// - 1: an object with the source platform and tracks
+/// - 2: version 1 with the `version` field
+const SCHEMA_VERSION: u64 = 2;
+
+/// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Playlist as written to disk, tagged with the schema version
+#[derive(Serialize)]
+struct SavedPlaylist<'a> {
+    version: u64,
+    #[serde(flatten)]
+    playlist: &'a Playlist,
+}
+
+impl Playlist {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let bytes = fs::read(path)?;
+        // Files written by `save_compressed` start with the gzip magic bytes whatever their name
+        let content = if bytes.starts_with(&GZIP_MAGIC) {
+            let mut content = String::new();
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut content)?;
+            content
+        } else {
+            String::from_utf8(bytes)?
+        };
+        let saved: Value = serde_json::from_str(&content)?;
+        let version = match &saved {
+            Value::Array(_) => 0,
+            saved => saved.get("version").and_then(Value::as_u64).unwrap_or(1),
+        };
+        Ok(match version {
+            // Files saved before the playlist recorded its platform hold only the tracks
+            0 => Playlist {
+                source_platform: Platform::Unknown,
+                tracks: serde_json::from_value(saved)?,
+                reported_total: None,
+            },
+            1..=SCHEMA_VERSION => serde_json::from_value(saved)?,
+            version => bail!(
+                "Playlist file has schema version {version}, this build reads up to {SCHEMA_VERSION}, update unilist to load it"
+            ),
+        })
+    }
+
+    /// Reads a spreadsheet export with a header row naming the `name`, `artist` and optional
+    /// `album` and `isrc` columns, in any order and case
+    pub fn load_csv(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = fs::read(path)?;
+        let mut reader = csv::Reader::from_reader(content.as_slice());
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name))
+        };
+        let (Some(name), Some(artist)) = (column("name"), column("artist")) else {
+            bail!("CSV header must name the name and artist columns, got: {headers:?}");
+        };
+        let album = column("album");
+        let isrc = column("isrc");
+
+        let tracks = reader
+            .records()
+            .map(|record| {
+                let record = record?;
+                let field = |idx: usize| record.get(idx).unwrap_or_default().trim().to_string();
+                Ok(Track {
+                    name: field(name),
+                    artist: field(artist),
+                    album: album.map(field).filter(|album| !album.is_empty()),
+                    isrc: isrc.map(field).filter(|isrc| !isrc.is_empty()),
+                    ..Default::default()
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Playlist {
+            source_platform: Platform::Unknown,
+            tracks,
+            reported_total: None,
+        })
+    }
+
+    /// Reads one `Artist - Title` query per line, skipping blank and `#` lines
+    ///
+    /// Lines are split on their first ` - `, lines without one are logged and skipped.
+    ///
+    /// ```
+    /// let playlist = unilist::Playlist::from_queries("Daft Punk - One More Time\nno dash here\n");
+    /// assert_eq!(playlist.tracks.len(), 1);
+    /// assert_eq!(playlist.tracks[0].artist, "Daft Punk");
+    /// assert_eq!(playlist.tracks[0].name, "One More Time");
+    /// ```
+    pub fn from_queries(content: &str) -> Self {
+        let tracks = content
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|(number, line)| {
+                let parsed = line
+                    .split_once(" - ")
+                    .map(|(artist, name)| (artist.trim(), name.trim()))
+                    .filter(|(artist, name)| !artist.is_empty() && !name.is_empty());
+                let Some((artist, name)) = parsed else {
+                    log::warn!("Skipping line {number}, expected Artist - Title: {line}");
+                    return None;
+                };
+                Some(Track {
+                    name: name.to_string(),
+                    artist: artist.to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        Playlist {
+            source_platform: Platform::Unknown,
+            tracks,
+            reported_total: None,
+        }
+    }
+
+    /// Writes the playlist as JSON, gzip-compressed if the file name ends with `.gz`
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|extension| extension == "gz") {
+            return self.save_compressed(path);
+        }
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Writes the playlist as gzip-compressed JSON, which [`Playlist::load`] reads back as is
+    pub fn save_compressed(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let mut encoder = GzEncoder::new(fs::File::create(path)?, Compression::default());
+        encoder.write_all(self.to_json()?.as_bytes())?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Versioned JSON written by [`Playlist::save`] and read back by [`Playlist::load`]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(&SavedPlaylist {
+            version: SCHEMA_VERSION,
+            playlist: self,
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Items between two lines of [`Progress::Plain`]
+const PLAIN_EVERY: u64 = 10;
+
+/// How scraping and conversion progress is reported on stderr
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Progress {
+    /// Interactive progress bar
+    Bar,
+    /// Periodic single lines like `converted 45/120...`, readable in CI logs
+    Plain,
+    /// No progress output
+    #[default]
+    None,
+}
+
+/// Progress of one stage, safe to advance from several workers
+pub(crate) struct Reporter {
+    progress: Progress,
+    /// Past tense of the stage's action, e.g. `scraped`
+    verb: &'static str,
+    total: Option<u64>,
+    done: AtomicU64,
+    bar: Option<ProgressBar>,
+}
+
+impl Reporter {
+    /// Starts reporting a stage, `total` is `None` while the item count is unknown
+    pub fn new(progress: Progress, verb: &'static str, total: Option<u64>) -> Self {
+        let bar = (progress == Progress::Bar).then(|| match total {
+            Some(total) => ProgressBar::new(total).with_style(
+                ProgressStyle::with_template("{msg} {wide_bar} {pos}/{len}")
+                    .expect("Valid progress template"),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {msg} {pos} tracks")
+                    .expect("Valid progress template"),
+            ),
+        });
+        if let Some(bar) = &bar {
+            bar.set_message(verb);
+        }
+        Self {
+            progress,
+            verb,
+            total,
+            done: AtomicU64::new(0),
+            bar,
+        }
+    }
+
+    /// Marks `count` more items as done
+    pub fn advance(&self, count: u64) {
+        let before = self.done.fetch_add(count, Ordering::Relaxed);
+        let done = before + count;
+        match self.progress {
+            Progress::Bar => {
+                if let Some(bar) = &self.bar {
+                    bar.inc(count);
+                }
+            }
+            Progress::Plain if done / PLAIN_EVERY > before / PLAIN_EVERY => self.print(done),
+            Progress::Plain | Progress::None => {}
+        }
+    }
+
+    /// Ends the stage, printing the final count
+    pub fn finish(&self) {
+        let done = self.done.load(Ordering::Relaxed);
+        match self.progress {
+            Progress::Bar => {
+                if let Some(bar) = &self.bar {
+                    bar.finish();
+                }
+            }
+            Progress::Plain if !done.is_multiple_of(PLAIN_EVERY) || done == 0 => self.print(done),
+            Progress::Plain | Progress::None => {}
+        }
+    }
+
+    fn print(&self, done: u64) {
+        match self.total {
+            Some(total) => eprintln!("{} {done}/{total}...", self.verb),
+            None => eprintln!("{} {done} tracks...", self.verb),
+        }
+    }
+}
@@ -0,0 +1,734 @@
+mod apple;
+mod spotify;
+
+pub(crate) use apple::apple_recording;
+
+use clap::ValueEnum;
+use headless_chrome::Tab;
+
+use crate::{
+    converter::Options,
+    normalize::{nfc, normalize_artist, normalize_title},
+    Platform, Track, UnsupportedPlatform,
+};
+
+/// Share of the confidence coming from the title, the rest comes from the artist
+const TITLE_WEIGHT: f64 = 0.6;
+
+/// Share of the confidence coming from the song lengths when both are known
+const DURATION_WEIGHT: f64 = 0.15;
+
+/// Seconds two lengths may differ by and still count as the same, platforms round differently
+const DURATION_TOLERANCE_SECS: u32 = 2;
+
+/// Seconds past the tolerance at which the lengths stop resembling each other at all
+const DURATION_SPREAD_SECS: f64 = 30.0;
+
+/// Searches the target platform for songs resembling a track in the given tab
+pub(crate) type Search = fn(&Tab, &Track, &Options) -> anyhow::Result<Vec<Candidate>>;
+
+pub(crate) fn searcher(target: &Platform) -> anyhow::Result<Search> {
+    match target {
+        Platform::Apple => Ok(apple::search_apple),
+        Platform::Spotify => Ok(spotify::search_spotify),
+        &platform => Err(UnsupportedPlatform::Target(platform).into()),
+    }
+}
+
+/// Reads the details of the song behind a link found on the target platform
+pub(crate) type Lookup = fn(&Tab, &str, &Options) -> anyhow::Result<Metadata>;
+
+pub(crate) fn lookup(target: &Platform) -> anyhow::Result<Lookup> {
+    match target {
+        Platform::Apple => Ok(apple::apple_metadata),
+        Platform::Spotify => Ok(spotify::spotify_metadata),
+        &platform => Err(UnsupportedPlatform::Target(platform).into()),
+    }
+}
+
+/// Details of a matched song as its target platform page shows them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Metadata {
+    pub album: Option<String>,
+    pub duration_secs: Option<u32>,
+    pub year: Option<u16>,
+}
+
+impl Metadata {
+    /// Overwrites the track's fields with the ones the page shows, keeping the others
+    pub fn apply(self, track: &mut Track) {
+        track.album = self.album.or(track.album.take());
+        track.duration_secs = self.duration_secs.or(track.duration_secs);
+        track.year = self.year.or(track.year);
+    }
+}
+
+/// Year of a release date like `2011-05-02`
+fn parse_year(date: &str) -> Option<u16> {
+    date.get(..4)?.parse().ok()
+}
+
+/// Search page of the `target` platform listing songs resembling `track`
+///
+/// The query is encoded, so titles holding `/`, `&`, `#` or `?` stay in one search term:
+///
+/// ```
+/// use unilist::{search_url, Platform, Track};
+///
+/// let track = Track {
+///     name: "AC/DC & Friends?".to_string(),
+///     artist: "Band".to_string(),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     search_url(&Platform::Apple, &track, "gb")?,
+///     "https://music.apple.com/gb/search?term=AC%2FDC+%26+Friends%3F+-+Band"
+/// );
+/// assert_eq!(
+///     search_url(&Platform::Spotify, &track, "gb")?,
+///     "https://open.spotify.com/search/AC%2FDC%20&%20Friends%3F%20Band/tracks"
+/// );
+/// assert!(search_url(&Platform::Youtube, &track, "gb").is_err());
+/// # anyhow::Ok(())
+/// ```
+pub fn search_url(
+    target: &Platform,
+    track: &Track,
+    region: &str,
+) -> Result<String, UnsupportedPlatform> {
+    match target {
+        Platform::Apple => Ok(apple::apple_search_url(region, track).into()),
+        // Spotify picks the storefront from the session instead of the URL
+        Platform::Spotify => Ok(spotify::spotify_search_url(track).into()),
+        &platform => Err(UnsupportedPlatform::Target(platform)),
+    }
+}
+
+/// Song found in the target platform's search results
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub title: String,
+    pub artist: String,
+    pub link: String,
+    /// Position on its album when the results show it
+    pub track_number: Option<u32>,
+    /// Marked with the explicit badge
+    pub explicit: bool,
+    /// Release year when the results show it
+    pub year: Option<u16>,
+    /// Album of the song when the results show it, Apple's search doesn't
+    pub album: Option<String>,
+    /// Length of the song when the results show it
+    pub duration_secs: Option<u32>,
+}
+
+/// Candidate the scored matcher weighed for a track, a row of [`match_report_csv`](crate::match_report_csv)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsideredCandidate {
+    /// Track as searched, its artist emptied by a [`SearchQuery::TitleOnly`] search and swapped
+    /// with its name by a [`SearchQuery::Swapped`] one
+    pub track: Track,
+    pub query: SearchQuery,
+    pub candidate: Candidate,
+    pub score: Score,
+    /// Picked as the track's match
+    pub chosen: bool,
+}
+
+/// Song picked on the target platform for a track
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub link: String,
+    /// Weighted similarity to the track in `0..=1`, see [`Score::confidence`]
+    pub confidence: f64,
+    /// Search that returned the song
+    pub query: SearchQuery,
+}
+
+/// Terms of the target platform search
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SearchQuery {
+    /// Title and artist together
+    #[default]
+    TitleAndArtist,
+    /// Title alone, used when the combined search returned nothing
+    TitleOnly,
+    /// Title and artist exchanged, for rows scraped with their columns reversed
+    Swapped,
+}
+
+impl SearchQuery {
+    /// Track searched in place of `track`, the search pages leave out an empty artist
+    pub(crate) fn query(&self, track: &Track) -> Track {
+        match self {
+            SearchQuery::TitleAndArtist => track.clone(),
+            SearchQuery::TitleOnly => Track {
+                artist: String::new(),
+                ..track.clone()
+            },
+            SearchQuery::Swapped => Track {
+                name: track.artist.clone(),
+                artist: track.name.clone(),
+                ..track.clone()
+            },
+        }
+    }
+}
+
+/// Longest single-word title counted as ambiguous, in characters
+const AMBIGUOUS_TITLE_LEN: usize = 3;
+
+/// Lowest artist similarity a candidate for an ambiguous title needs under
+/// [`AmbiguousPolicy::Strict`]
+const AMBIGUOUS_ARTIST_THRESHOLD: f64 = 0.9;
+
+/// Lowest length similarity a candidate for an ambiguous title needs under
+/// [`AmbiguousPolicy::Strict`] when both lengths are known, a few seconds apart at most
+const AMBIGUOUS_DURATION_THRESHOLD: f64 = 0.9;
+
+/// Titles like `1` or `Go`, whose search results hold countless unrelated songs
+///
+/// ```
+/// use unilist::is_ambiguous_title;
+///
+/// assert!(is_ambiguous_title("Go"));
+/// assert!(is_ambiguous_title("1 (Remastered)"));
+/// assert!(!is_ambiguous_title("Go West"));
+/// ```
+pub fn is_ambiguous_title(title: &str) -> bool {
+    let title = normalize_title(title);
+    !title.contains(' ') && title.chars().count() <= AMBIGUOUS_TITLE_LEN
+}
+
+/// How tracks with an [ambiguous title](is_ambiguous_title) are matched
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum AmbiguousPolicy {
+    /// Only accept songs of the exact title whose artist is nearly identical, and whose length
+    /// is too when both are known
+    #[default]
+    Strict,
+    /// Leave them unmatched for review
+    Skip,
+    /// Leave them to the user, who picks among the search results once the conversion ends
+    Interactive,
+}
+
+impl AmbiguousPolicy {
+    /// Drops the candidates the policy rejects for `track`, all of them under `Skip` and
+    /// `Interactive`
+    pub(crate) fn filter(
+        &self,
+        candidates: &mut Vec<Candidate>,
+        track: &Track,
+        similarity: Similarity,
+    ) {
+        if !is_ambiguous_title(&track.name) {
+            return;
+        }
+        match self {
+            AmbiguousPolicy::Strict => candidates.retain(|candidate| {
+                let score = Score::of(candidate, track, similarity);
+                normalize_title(&candidate.title) == normalize_title(&track.name)
+                    && score.artist >= AMBIGUOUS_ARTIST_THRESHOLD
+                    && score
+                        .duration
+                        .is_none_or(|duration| duration >= AMBIGUOUS_DURATION_THRESHOLD)
+            }),
+            AmbiguousPolicy::Skip | AmbiguousPolicy::Interactive => candidates.clear(),
+        }
+    }
+}
+
+/// Lowest similarity of the normalized album names counting as the same album
+const ALBUM_MATCH_THRESHOLD: f64 = 0.9;
+
+/// Confidence the scored matcher adds to candidates from the track's album under
+/// [`AlbumMode::Soft`], enough to win among near ties but not to pass a worse title or artist
+const ALBUM_BONUS: f64 = 0.05;
+
+/// How the album of a track weighs on its match
+///
+/// Albums are named differently across platforms, localized or as reissues and deluxe editions,
+/// so a mismatch alone rarely means the wrong song.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum AlbumMode {
+    /// Don't compare albums
+    Ignore,
+    /// Favor songs from the same album among similar scored candidates, never rejecting any
+    #[default]
+    Soft,
+    /// Only accept songs from the same album, tracks without an album match as usual; misses
+    /// on targets whose results don't show albums, like Apple Music
+    Strict,
+}
+
+impl AlbumMode {
+    /// Drops the candidates from other albums under `Strict`
+    pub(crate) fn filter(
+        &self,
+        candidates: &mut Vec<Candidate>,
+        track: &Track,
+        similarity: Similarity,
+    ) {
+        if *self == AlbumMode::Strict && track.album.is_some() {
+            candidates.retain(|candidate| same_album(candidate, track, similarity));
+        }
+    }
+
+    /// Confidence added to the candidate's when picking among the accepted ones
+    fn bonus(&self, candidate: &Candidate, track: &Track, similarity: Similarity) -> f64 {
+        match self {
+            AlbumMode::Soft if same_album(candidate, track, similarity) => ALBUM_BONUS,
+            _ => 0.0,
+        }
+    }
+}
+
+fn same_album(candidate: &Candidate, track: &Track, similarity: Similarity) -> bool {
+    match (&candidate.album, &track.album) {
+        (Some(found), Some(album)) => {
+            similarity.compare(&normalize_title(found), &normalize_title(album))
+                >= ALBUM_MATCH_THRESHOLD
+        }
+        _ => false,
+    }
+}
+
+/// Artists of a track's artist line that are searched and scored
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ArtistSelection {
+    /// The whole line, collaborators included
+    #[default]
+    All,
+    /// Only the first artist, for collaborations the target credits differently
+    Primary,
+}
+
+impl ArtistSelection {
+    /// Track as matched, its artist line cut down to the selected artists
+    ///
+    /// ```
+    /// use unilist::{ArtistSelection, Track};
+    ///
+    /// let track = Track {
+    ///     name: "Song".to_string(),
+    ///     artist: "Main, Guest & Other".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(ArtistSelection::Primary.select(&track).artist, "Main");
+    /// assert_eq!(ArtistSelection::All.select(&track).artist, "Main, Guest & Other");
+    ///
+    /// let duo = Track {
+    ///     name: "Song".to_string(),
+    ///     artist: "Simon & Garfunkel, Guest".to_string(),
+    ///     artists: vec!["Simon & Garfunkel".to_string(), "Guest".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(ArtistSelection::Primary.select(&duo).artist, "Simon & Garfunkel");
+    /// ```
+    pub fn select(&self, track: &Track) -> Track {
+        match self {
+            ArtistSelection::All => track.clone(),
+            ArtistSelection::Primary => {
+                let primary = track.artist_names().into_iter().next().unwrap_or_default();
+                Track {
+                    artist: primary.clone(),
+                    artists: vec![primary],
+                    ..track.clone()
+                }
+            }
+        }
+    }
+}
+
+/// How closely a candidate resembles the searched track, every component is in `0..=1`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub title: f64,
+    pub artist: f64,
+    /// Closeness of the song lengths, `None` unless the track's and the candidate's are known
+    pub duration: Option<f64>,
+}
+
+impl Score {
+    pub fn of(candidate: &Candidate, track: &Track, similarity: Similarity) -> Self {
+        Self {
+            title: similarity.compare(
+                &normalize_title(&candidate.title),
+                &normalize_title(&track.name),
+            ),
+            artist: similarity.compare(
+                &normalize_artist(&candidate.artist),
+                &normalize_artist(&track.artist),
+            ),
+            duration: candidate
+                .duration_secs
+                .zip(track.duration_secs)
+                .map(|(a, b)| duration_similarity(a, b)),
+        }
+    }
+
+    /// Weighted total of the components, the lengths count only when both are known
+    ///
+    /// ```
+    /// use unilist::{Candidate, Score, Similarity, Track};
+    ///
+    /// let track = Track {
+    ///     name: "Halo".to_string(),
+    ///     artist: "Beyoncé".to_string(),
+    ///     duration_secs: Some(261),
+    ///     ..Default::default()
+    /// };
+    /// let candidate = |duration_secs| Candidate {
+    ///     title: "Halo".to_string(),
+    ///     artist: "Beyoncé".to_string(),
+    ///     link: "https://open.spotify.com/track/1".to_string(),
+    ///     track_number: None,
+    ///     explicit: false,
+    ///     year: None,
+    ///     album: None,
+    ///     duration_secs,
+    /// };
+    /// let score = |duration_secs| Score::of(&candidate(duration_secs), &track, Similarity::default());
+    /// assert_eq!(score(Some(262)).confidence(), 1.0);
+    /// assert!(score(Some(300)).confidence() < 0.9);
+    /// assert_eq!(score(None).duration, None);
+    /// assert_eq!(score(None).confidence(), 1.0);
+    /// ```
+    pub fn confidence(&self) -> f64 {
+        let text = TITLE_WEIGHT * self.title + (1.0 - TITLE_WEIGHT) * self.artist;
+        match self.duration {
+            Some(duration) => (1.0 - DURATION_WEIGHT) * text + DURATION_WEIGHT * duration,
+            None => text,
+        }
+    }
+}
+
+/// 1 for lengths within the tolerance, falling to 0 as they drift further apart
+fn duration_similarity(a: u32, b: u32) -> f64 {
+    let delta = a.abs_diff(b).saturating_sub(DURATION_TOLERANCE_SECS);
+    1.0 - (f64::from(delta) / DURATION_SPREAD_SECS).min(1.0)
+}
+
+/// Fuzzy metric comparing the normalized titles and artists
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Similarity {
+    /// Favors strings sharing their start, forgiving for suffixes like "(Live)"
+    #[default]
+    JaroWinkler,
+    /// Edit distance relative to the longer string
+    Levenshtein,
+    /// Edit distance of the words sorted, so "Last Song, The" equals "The Last Song"
+    TokenSort,
+}
+
+impl Similarity {
+    /// Similarity of the two strings in `0..=1`
+    ///
+    /// ```
+    /// use unilist::Similarity;
+    ///
+    /// assert_eq!(Similarity::TokenSort.compare("last song, the", "the last song"), 1.0);
+    /// assert!(Similarity::Levenshtein.compare("last song, the", "the last song") < 0.5);
+    /// ```
+    pub fn compare(&self, a: &str, b: &str) -> f64 {
+        match self {
+            Similarity::JaroWinkler => strsim::jaro_winkler(a, b),
+            Similarity::Levenshtein => strsim::normalized_levenshtein(a, b),
+            Similarity::TokenSort => {
+                strsim::normalized_levenshtein(&sorted_tokens(a), &sorted_tokens(b))
+            }
+        }
+    }
+}
+
+/// Lowercase words of the text in alphabetical order
+fn sorted_tokens(text: &str) -> String {
+    let lowercase = text.to_lowercase();
+    let mut tokens = lowercase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+    tokens.sort_unstable();
+    tokens.join(" ")
+}
+
+/// Source of songs on the target platform, see
+/// [`Converter::with_resolver`](crate::Converter::with_resolver)
+///
+/// Resolvers run in the order they were added, each on the tracks the earlier ones missed. Their
+/// results go through the same filters and [`Matcher`] as the browser search, itself a resolver.
+pub trait TrackResolver: Send + Sync {
+    /// Name shown in the logs, e.g. `spotify-api`
+    fn name(&self) -> &str;
+
+    /// Whether the resolver can search `target`, the converter skips it otherwise
+    fn supports(&self, target: &Platform) -> bool;
+
+    /// Songs on `target` resembling `track`
+    fn search(&self, track: &Track, target: &Platform) -> anyhow::Result<Vec<Candidate>>;
+}
+
+/// Strategy for picking a song among the target platform's search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Matcher {
+    /// Song title must equal the track name, ignoring case
+    #[default]
+    Exact,
+    /// Take the first song in the results
+    First,
+    /// Take the most similar song reaching the minimum confidence, title and artist similarity
+    Scored,
+}
+
+impl Matcher {
+    pub(crate) fn pick(
+        &self,
+        candidates: Vec<Candidate>,
+        track: &Track,
+        options: &Options,
+    ) -> Option<Candidate> {
+        match self {
+            Matcher::Exact => options.prefer.choose(
+                candidates
+                    .into_iter()
+                    .filter(|candidate| {
+                        nfc(&candidate.title).to_lowercase() == nfc(&track.name).to_lowercase()
+                    })
+                    .collect(),
+            ),
+            Matcher::First => candidates.into_iter().next(),
+            Matcher::Scored => {
+                let mut accepted = rank(candidates, track, options.similarity)
+                    .into_iter()
+                    .filter(|(_, score)| {
+                        score.confidence() >= options.min_confidence
+                            && score.title >= options.title_threshold
+                            && score.artist >= options.artist_threshold
+                    })
+                    .map(|(candidate, score)| {
+                        let bonus = options
+                            .album_mode
+                            .bonus(&candidate, track, options.similarity);
+                        (candidate, score.confidence() + bonus)
+                    })
+                    .collect::<Vec<_>>();
+                // Stable, the ranking's tiebreakers still order equal totals
+                accepted.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+                let best = accepted.first()?.1;
+                let tied = accepted
+                    .into_iter()
+                    .take_while(|(_, total)| *total == best)
+                    .map(|(candidate, _)| candidate)
+                    .collect();
+                options.prefer.choose(tied)
+            }
+        }
+    }
+}
+
+/// Version picked among otherwise equally good candidates
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum VersionPreference {
+    /// Keep the first of the tied songs
+    #[default]
+    Either,
+    /// Prefer songs with the explicit badge
+    Explicit,
+    /// Prefer songs without the explicit badge
+    Clean,
+}
+
+impl VersionPreference {
+    /// Picks the first preferred song of the tied ones, or the first one if none is preferred
+    fn choose(&self, mut tied: Vec<Candidate>) -> Option<Candidate> {
+        let preferred = match self {
+            VersionPreference::Either => None,
+            VersionPreference::Explicit => tied.iter().position(|candidate| candidate.explicit),
+            VersionPreference::Clean => tied.iter().position(|candidate| !candidate.explicit),
+        };
+        match preferred {
+            Some(idx) => Some(tied.swap_remove(idx)),
+            None => tied.into_iter().next(),
+        }
+    }
+}
+
+/// Looser matching tried when retrying the tracks the first pass didn't match
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Relaxation {
+    /// Subtracted from every threshold of the scored matcher
+    pub threshold_drop: f64,
+    /// Search the normalized title and the artist without featured ones instead of the scraped
+    /// ones
+    pub normalized_query: bool,
+    /// Let songs from other albums and years match, `AlbumMode::Strict` falls back to `Soft`
+    pub loose_album_and_year: bool,
+}
+
+/// Retry levels, from the slightest relaxation to the loosest
+pub(crate) const RELAXATIONS: &[Relaxation] = &[
+    Relaxation {
+        threshold_drop: 0.1,
+        normalized_query: false,
+        loose_album_and_year: false,
+    },
+    Relaxation {
+        threshold_drop: 0.1,
+        normalized_query: true,
+        loose_album_and_year: false,
+    },
+    Relaxation {
+        threshold_drop: 0.2,
+        normalized_query: true,
+        loose_album_and_year: false,
+    },
+    Relaxation {
+        threshold_drop: 0.2,
+        normalized_query: true,
+        loose_album_and_year: true,
+    },
+];
+
+impl Relaxation {
+    /// Scored matching with the lowered thresholds
+    pub fn options(&self, options: &Options) -> Options {
+        let album_mode = match options.album_mode {
+            AlbumMode::Strict if self.loose_album_and_year => AlbumMode::Soft,
+            mode => mode,
+        };
+        Options {
+            matcher: Matcher::Scored,
+            min_confidence: options.min_confidence - self.threshold_drop,
+            title_threshold: options.title_threshold - self.threshold_drop,
+            artist_threshold: options.artist_threshold - self.threshold_drop,
+            album_mode,
+            match_year: options.match_year && !self.loose_album_and_year,
+            ..options.clone()
+        }
+    }
+
+    /// Track searched in place of `track`
+    pub fn query(&self, track: &Track) -> Track {
+        let (name, artist) = if self.normalized_query {
+            (
+                normalize_title(&track.name),
+                normalize_artist(&track.artist),
+            )
+        } else {
+            (track.name.clone(), track.artist.clone())
+        };
+        Track {
+            name,
+            artist,
+            ..track.clone()
+        }
+    }
+}
+
+/// Whether the candidate shows the track's release year
+fn same_year(candidate: &Candidate, track: &Track) -> bool {
+    track.year.is_some() && candidate.year == track.year
+}
+
+/// Artist substrings of karaoke, tribute and cover uploads, dropped from the candidates unless
+/// told otherwise
+pub const DEFAULT_ARTIST_BLOCKLIST: &[&str] = &[
+    "karaoke",
+    "tribute",
+    "cover by",
+    "in the style of",
+    "made famous by",
+];
+
+/// Drops candidates whose artist contains a `blocklist` entry, ignoring case, unless the track's
+/// own artist does
+pub(crate) fn drop_blocked(candidates: &mut Vec<Candidate>, track: &Track, blocklist: &[String]) {
+    let own_artist = track.artist.to_lowercase();
+    let blocked = blocklist
+        .iter()
+        .map(|entry| entry.to_lowercase())
+        .filter(|entry| !entry.is_empty() && !own_artist.contains(entry))
+        .collect::<Vec<_>>();
+    candidates.retain(|candidate| {
+        let artist = candidate.artist.to_lowercase();
+        !blocked.iter().any(|entry| artist.contains(entry))
+    });
+}
+
+/// Moves candidates showing another release year than the track's behind the others, or drops
+/// them when `strict`
+pub(crate) fn prefer_year(candidates: &mut Vec<Candidate>, track: &Track, strict: bool) {
+    if track.year.is_none() {
+        return;
+    }
+    let other_year =
+        |candidate: &Candidate| candidate.year.is_some() && !same_year(candidate, track);
+    if strict {
+        candidates.retain(|candidate| !other_year(candidate));
+    } else {
+        candidates.sort_by_key(other_year);
+    }
+}
+
+/// Scores the candidates, most confident first, then matching track numbers and years first
+pub fn rank(
+    candidates: Vec<Candidate>,
+    track: &Track,
+    similarity: Similarity,
+) -> Vec<(Candidate, Score)> {
+    let mut ranked = candidates
+        .into_iter()
+        .map(|candidate| {
+            let score = Score::of(&candidate, track, similarity);
+            (candidate, score)
+        })
+        .collect::<Vec<_>>();
+    let same_number = |candidate: &Candidate| {
+        track.track_number.is_some() && candidate.track_number == track.track_number
+    };
+    ranked.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .confidence()
+            .total_cmp(&a_score.confidence())
+            .then_with(|| same_number(b).cmp(&same_number(a)))
+            .then_with(|| same_year(b, track).cmp(&same_year(a, track)))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_ambiguous_titles_need_a_matching_length_when_both_are_known() {
+        let track = Track {
+            name: "Go".to_string(),
+            artist: "Band".to_string(),
+            duration_secs: Some(200),
+            ..Default::default()
+        };
+        let candidate = |link: &str, duration_secs| Candidate {
+            title: "Go".to_string(),
+            artist: "Band".to_string(),
+            link: link.to_string(),
+            track_number: None,
+            explicit: false,
+            year: None,
+            album: None,
+            duration_secs,
+        };
+        let mut candidates = vec![
+            candidate("same", Some(201)),
+            candidate("longer", Some(260)),
+            candidate("unknown", None),
+        ];
+        AmbiguousPolicy::Strict.filter(&mut candidates, &track, Similarity::default());
+        let links = candidates
+            .iter()
+            .map(|c| c.link.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(links, ["same", "unknown"]);
+    }
+}
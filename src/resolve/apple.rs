@@ -0,0 +1,177 @@
+use anyhow::anyhow;
+use headless_chrome::Tab;
+use url::Url;
+
+use super::{parse_year, Candidate, Metadata};
+use crate::{
+    absolute_link,
+    browser::{dismiss_overlay, navigate_with, WaitStrategy},
+    converter::Options,
+    normalize::nfc,
+    AppleSelectors, Track,
+};
+
+/// Collects the songs Apple's search returns for `track`
+pub(crate) fn search_apple(
+    tab: &Tab,
+    track: &Track,
+    options: &Options,
+) -> anyhow::Result<Vec<Candidate>> {
+    let url = apple_search_url(&options.region, track);
+
+    // The reused tab still shows the previous results until the new page commits
+    navigate_with(tab, url.as_str(), WaitStrategy::Navigated, options)?;
+    try_collect_apple_candidates(tab, track, &options.selectors.apple)
+}
+
+/// Builds the search page URL, encoding the query so `/`, `&`, `#` and `?` in titles survive
+pub(super) fn apple_search_url(region: &str, track: &Track) -> Url {
+    let mut url = Url::parse("https://music.apple.com").expect("Static URL is valid");
+    url.path_segments_mut()
+        .expect("Static URL has a base")
+        .push(region)
+        .push("search");
+    let term = match track.artist.as_str() {
+        "" => track.name.clone(),
+        artist => format!("{} - {artist}", &track.name),
+    };
+    url.query_pairs_mut().append_pair("term", &nfc(&term));
+    url
+}
+
+fn try_collect_apple_candidates(
+    tab: &Tab,
+    track: &Track,
+    selectors: &AppleSelectors,
+) -> anyhow::Result<Vec<Candidate>> {
+    // Wait for whichever state comes first so misses don't burn the whole timeout
+    let section = tab.wait_for_element(&format!(
+        "{}, {}",
+        selectors.songs_section, selectors.no_results
+    ))?;
+    // The interstitial overlays the results, clicks and reads would hit it instead
+    if dismiss_overlay(tab, &selectors.open_in_app_dismiss) {
+        log::info!("Dismissed the Open in Music app prompt");
+    }
+    if tab.find_element(&selectors.no_results).is_ok() {
+        return Err(anyhow!("No results for {}", track.name));
+    }
+    // Result links are often relative like `/us/album/...`, resolved against the page shown
+    let base = tab
+        .get_url()
+        .parse()
+        .unwrap_or_else(|_| Url::parse("https://music.apple.com").expect("Static URL is valid"));
+
+    let candidates = section
+        .wait_for_elements("li")?
+        .into_iter()
+        .filter_map(|el| {
+            let link = el.find_element(&selectors.song_link).ok()?;
+            let title = link.get_inner_text().ok()?;
+            let href = link.get_attribute_value("href").ok()??;
+            let artist = el
+                .find_element(&selectors.song_artist)
+                .and_then(|el| el.get_inner_text())
+                .unwrap_or_default();
+            Some(Candidate {
+                title,
+                artist,
+                link: urlencoding::decode(&absolute_link(&base, &href)?)
+                    .ok()?
+                    .into_owned(),
+                track_number: None,
+                explicit: el.find_element(&selectors.explicit_badge).is_ok(),
+                year: None,
+                // Song lockups only show the artist
+                album: None,
+                duration_secs: None,
+            })
+        })
+        .collect();
+    Ok(candidates)
+}
+
+/// `MusicRecording` JSON-LD of the song page shown in the tab, `None` when it has none
+pub(crate) fn apple_recording(tab: &Tab) -> anyhow::Result<Option<serde_json::Value>> {
+    let recording = tab
+        .evaluate(
+            r#"JSON.stringify([...document.querySelectorAll('script[type="application/ld+json"]')]
+                .map(script => JSON.parse(script.textContent))
+                .find(data => data["@type"] === "MusicRecording") ?? null)"#,
+            false,
+        )?
+        .value
+        .and_then(|value| value.as_str().map(str::to_string));
+    match recording {
+        Some(recording) => Ok(serde_json::from_str(&recording)?),
+        None => Ok(None),
+    }
+}
+
+/// Reads the `MusicRecording` structured data Apple embeds in song pages
+pub(crate) fn apple_metadata(tab: &Tab, link: &str, options: &Options) -> anyhow::Result<Metadata> {
+    navigate_with(tab, link, WaitStrategy::Navigated, options)?;
+    let recording = apple_recording(tab)?.ok_or_else(|| anyhow!("No song data on {link}"))?;
+
+    Ok(Metadata {
+        album: recording["inAlbum"]["name"].as_str().map(str::to_string),
+        duration_secs: recording["duration"].as_str().and_then(parse_iso_duration),
+        year: recording["datePublished"].as_str().and_then(parse_year),
+    })
+}
+
+/// Seconds of an ISO 8601 duration like `PT3M29S`
+fn parse_iso_duration(duration: &str) -> Option<u32> {
+    let mut secs = 0;
+    let mut number = String::new();
+    for c in duration.strip_prefix("PT")?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'H' | 'M' | 'S' => {
+                let unit = match c {
+                    'H' => 3600,
+                    'M' => 60,
+                    _ => 1,
+                };
+                secs += number.parse::<u32>().ok()? * unit;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    Some(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(name: &str, artist: &str) -> Track {
+        Track {
+            name: name.to_string(),
+            artist: artist.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn search_url_encodes_slash() {
+        let url = apple_search_url("us", &track("Back in Black", "AC/DC"));
+        assert_eq!(
+            url.as_str(),
+            "https://music.apple.com/us/search?term=Back+in+Black+-+AC%2FDC"
+        );
+        assert_eq!(url.path(), "/us/search");
+    }
+
+    #[test]
+    fn search_url_keeps_ampersand_in_the_term() {
+        let url = apple_search_url("us", &track("Q&A", "Band"));
+        assert_eq!(
+            url.as_str(),
+            "https://music.apple.com/us/search?term=Q%26A+-+Band"
+        );
+        let pairs = url.query_pairs().collect::<Vec<_>>();
+        assert_eq!(pairs, [("term".into(), "Q&A - Band".into())]);
+    }
+}
@@ -0,0 +1,98 @@
+use headless_chrome::Tab;
+use url::Url;
+
+use super::{parse_year, Candidate, Metadata};
+use crate::{
+    browser::{navigate_with, WaitStrategy},
+    converter::Options,
+    normalize::{nfc, parse_clock},
+    SpotifySelectors, Track,
+};
+
+/// Collects the songs Spotify's search returns for `track`
+pub(crate) fn search_spotify(
+    tab: &Tab,
+    track: &Track,
+    options: &Options,
+) -> anyhow::Result<Vec<Candidate>> {
+    let url = spotify_search_url(track);
+
+    // The reused tab still shows the previous results until the new page commits
+    navigate_with(tab, url.as_str(), WaitStrategy::Navigated, options)?;
+    try_collect_spotify_candidates(tab, &options.selectors.spotify)
+}
+
+/// Builds the song search page URL, the query is a single encoded path segment
+pub(super) fn spotify_search_url(track: &Track) -> Url {
+    let mut url = Url::parse("https://open.spotify.com").expect("Static URL is valid");
+    url.path_segments_mut()
+        .expect("Static URL has a base")
+        .push("search")
+        .push(&nfc(format!("{} {}", &track.name, &track.artist).trim_end()))
+        .push("tracks");
+    url
+}
+
+fn try_collect_spotify_candidates(
+    tab: &Tab,
+    selectors: &SpotifySelectors,
+) -> anyhow::Result<Vec<Candidate>> {
+    let base = Url::parse("https://open.spotify.com").expect("Static URL is valid");
+
+    let candidates = tab
+        .wait_for_elements(&selectors.search_row)?
+        .into_iter()
+        .filter_map(|el| {
+            let link = el.find_element(&selectors.track_link).ok()?;
+            let title = link.find_element("div").ok()?.get_inner_text().ok()?;
+            let href = link.get_attribute_value("href").ok()??;
+            let artist = el
+                .find_element(&selectors.artist_link)
+                .and_then(|el| el.get_inner_text())
+                .unwrap_or_default();
+            Some(Candidate {
+                title,
+                artist,
+                link: base.join(&href).ok()?.into(),
+                track_number: None,
+                explicit: el.find_element(&selectors.explicit_badge).is_ok(),
+                year: None,
+                album: el
+                    .find_element(&selectors.album_link)
+                    .and_then(|el| el.get_inner_text())
+                    .ok(),
+                // The length is the last column, its text the only one shaped like `3:45`
+                duration_secs: el
+                    .get_inner_text()
+                    .ok()
+                    .and_then(|text| text.split_whitespace().rev().find_map(parse_clock)),
+            })
+        })
+        .collect();
+    Ok(candidates)
+}
+
+/// Reads the song page's `music:` meta tags and the link to its album
+pub(crate) fn spotify_metadata(
+    tab: &Tab,
+    link: &str,
+    options: &Options,
+) -> anyhow::Result<Metadata> {
+    navigate_with(tab, link, WaitStrategy::Navigated, options)?;
+    let meta = |name: &str| {
+        tab.find_element(&format!(r#"meta[name="{name}"]"#))
+            .and_then(|el| el.get_attribute_value("content"))
+            .ok()
+            .flatten()
+    };
+    let album = tab
+        .wait_for_element(r#"a[href*="/album/"]"#)
+        .and_then(|el| el.get_inner_text())
+        .ok();
+
+    Ok(Metadata {
+        album,
+        duration_secs: meta("music:duration").and_then(|secs| secs.parse().ok()),
+        year: meta("music:release_date").as_deref().and_then(parse_year),
+    })
+}
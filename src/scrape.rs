@@ -0,0 +1,152 @@
+mod apple;
+mod song;
+mod spotify;
+mod youtube;
+
+pub(crate) use apple::fetch_apple_playlist;
+#[cfg(feature = "bench")]
+pub use apple::parse_apple_row;
+#[cfg(feature = "bench")]
+pub use song::parse_apple_recording;
+pub(crate) use song::{fetch_song, fetch_video};
+pub(crate) use spotify::fetch_spotify_playlist;
+#[cfg(feature = "bench")]
+pub use spotify::parse_spotify_row;
+pub(crate) use youtube::fetch_yt_playlist;
+#[cfg(feature = "bench")]
+pub use youtube::parse_yt_row;
+
+use anyhow::anyhow;
+use headless_chrome::{Element, Tab};
+
+use url::Url;
+
+use crate::{converter::Options, Event, Platform, PlaylistError, Track};
+
+/// Messages the platforms show instead of a playlist's rows, matched case-insensitively
+const PLAYLIST_MESSAGES: &[(Platform, &str, PlaylistError)] = &[
+    (
+        Platform::Spotify,
+        "couldn't find that playlist",
+        PlaylistError::NotFound,
+    ),
+    (
+        Platform::Spotify,
+        "this playlist is private",
+        PlaylistError::Private,
+    ),
+    (
+        Platform::Apple,
+        "this playlist is not available",
+        PlaylistError::NotFound,
+    ),
+    (Platform::Apple, "can't be found", PlaylistError::NotFound),
+    (
+        Platform::Youtube,
+        "this playlist is private",
+        PlaylistError::Private,
+    ),
+    (
+        Platform::Youtube,
+        "playlist does not exist",
+        PlaylistError::NotFound,
+    ),
+    (
+        Platform::Youtube,
+        "playlist doesn't exist",
+        PlaylistError::NotFound,
+    ),
+];
+
+/// Explains rows that never showed up with the page's own message, when it has one
+pub(crate) fn explain_missing_rows(
+    tab: &Tab,
+    platform: Platform,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    let text = tab
+        .find_element("body")
+        .and_then(|body| body.get_inner_text())
+        .unwrap_or_default()
+        .to_lowercase();
+    let explained = PLAYLIST_MESSAGES
+        .iter()
+        .find(|(on, message, _)| *on == platform && text.contains(message));
+    match explained {
+        Some(&(_, _, playlist_error)) => error.context(playlist_error),
+        None => error,
+    }
+}
+
+/// Logs a scraped track as a `track_scraped` event, and streams it if asked to
+pub(crate) fn log_scraped(track: &Track, options: &Options) {
+    if options.event_stream {
+        Event::Scraped { track }.emit();
+    }
+    log::info!(
+        event = "track_scraped",
+        name = track.name.as_str(),
+        artist = track.artist.as_str();
+        "Scraped {} — {}", track.name, track.artist
+    );
+}
+
+/// Release year among the words of a header like `ROCK · 2011 · LOSSLESS`
+pub(crate) fn header_year(text: &str) -> Option<u16> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|word| word.len() == 4)
+        .filter_map(|word| word.parse().ok())
+        .find(|year| (1900..=2100).contains(year))
+}
+
+/// Absolute target of the first link matching `selector` in a row
+pub(crate) fn row_link(row: &Element, selector: &str, base: &str) -> Option<String> {
+    let href = row
+        .find_element(selector)
+        .ok()?
+        .get_attribute_value("href")
+        .ok()??;
+    let base = Url::parse(base).expect("Static URL is valid");
+    Some(base.join(&href).ok()?.into())
+}
+
+/// Source of the album art in a row, `None` while the lazy loaded image still shows a placeholder
+pub(crate) fn cover_url(row: &Element) -> Option<String> {
+    row.find_element("img")
+        .ok()?
+        .get_attribute_value("src")
+        .ok()
+        .flatten()
+        .filter(|src| src.starts_with("http"))
+}
+
+#[allow(dead_code)] // Could be useful in the impl of the other music sources
+pub(crate) fn get_body_scroll_height(tab: &Tab) -> anyhow::Result<u64> {
+    tab.evaluate("document.body.scrollHeight", true)
+        .ok()
+        .and_then(|obj| match obj.value {
+            Some(serde_json::Value::Number(height)) => height.as_u64(),
+            unknown => panic!("Unknown height type: {unknown:?}"),
+        })
+        .ok_or(anyhow!("Failed to get height"))
+}
+
+/// Track count of a header line like `50 songs, 3 hr 2 min`, `12 Songs` or `1,024 videos`
+pub(crate) fn header_total(text: &str) -> Option<usize> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    words.windows(2).find_map(|pair| {
+        let unit = pair[1].to_lowercase();
+        let counts_tracks = ["song", "track", "video", "episode"]
+            .iter()
+            .any(|counted| unit.starts_with(counted));
+        let number = pair[0].replace([',', '.'], "");
+        counts_tracks.then(|| number.parse().ok()).flatten()
+    })
+}
+
+/// Track count of the first header matching `selector`, `None` without one or in localized
+/// storefronts
+pub(crate) fn reported_total(tab: &Tab, selector: &str) -> Option<usize> {
+    let text = tab.find_element(selector).ok()?.get_inner_text().ok()?;
+    header_total(&text)
+}
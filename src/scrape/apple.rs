@@ -0,0 +1,154 @@
+use anyhow::bail;
+use headless_chrome::Browser;
+use url::Url;
+
+use crate::{
+    browser::{dismiss_overlay, open_page, screenshot_on_error},
+    converter::Options,
+    progress::Reporter,
+    Platform, Playlist, PlaylistError, Track,
+};
+
+/// Song row of album and playlist pages
+const APPLE_ROW: &str = r#"div[role="row"]"#;
+
+/// Song name of a row
+const APPLE_ROW_NAME: &str = ".songs-list-row__song-name";
+
+/// Link to the song, either its own page or its position on the album
+const APPLE_ROW_LINK: &str = r#"a[href*="/song/"], a[href*="?i="]"#;
+
+/// Artist column of a row, only playlists and compilations fill it
+const APPLE_ROW_ARTIST: &str = ".songs-list-row__by-line";
+
+/// Position column of the rows
+const APPLE_ROW_NUMBER: &str = ".songs-list-row__rank";
+
+/// Album or playlist title of the page header
+const APPLE_HEADER_TITLE: &str = ".headings__title";
+
+/// Genre, year and format line of an album header
+const APPLE_HEADER_METADATA: &str = ".headings__metadata-bottom";
+
+/// Line under the songs counting them, like `12 songs, 45 minutes`
+const APPLE_FOOTER_TOTAL: &str = r#"[data-testid="tracklist-footer-description"]"#;
+
+/// Artist shared by every song of an album, shown under the header title
+const APPLE_HEADER_ARTIST: &str = ".headings__subtitles";
+
+/// Collects the songs of an Apple Music album or playlist page
+pub(crate) fn fetch_apple_playlist(
+    browser: &Browser,
+    url: &Url,
+    options: &Options,
+) -> anyhow::Result<Playlist> {
+    let segments = url
+        .path_segments()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if segments.contains(&"library") {
+        bail!("Apple Music library pages need a signed in account, share the album or playlist instead");
+    }
+    let is_album = segments.contains(&"album");
+
+    let tab = open_page(browser, url.as_str(), Platform::Apple, options)?;
+    if dismiss_overlay(&tab, &options.selectors.apple.open_in_app_dismiss) {
+        log::info!("Dismissed the Open in Music app prompt");
+    }
+
+    let header_text = |selector| {
+        tab.find_element(selector)
+            .and_then(|el| el.get_inner_text())
+            .ok()
+            .map(|text| text.trim().to_string())
+    };
+    let album = is_album.then(|| header_text(APPLE_HEADER_TITLE)).flatten();
+    let year = is_album
+        .then(|| header_text(APPLE_HEADER_METADATA))
+        .flatten()
+        .and_then(|text| super::header_year(&text));
+    // Playlist headers name the curator rather than an artist
+    let header_artist = (is_album && options.artist_from_album_header)
+        .then(|| header_text(APPLE_HEADER_ARTIST))
+        .flatten();
+
+    let rows = screenshot_on_error(&tab, options, tab.wait_for_elements(APPLE_ROW))
+        .map_err(|e| super::explain_missing_rows(&tab, Platform::Apple, e))?;
+    let reported_total = super::reported_total(&tab, APPLE_FOOTER_TOTAL);
+    let mut tracks = rows
+        .into_iter()
+        .filter_map(|el| {
+            let text = |selector| {
+                el.find_element(selector)
+                    .and_then(|el| el.get_inner_text())
+                    .ok()
+            };
+            // Albums number their songs, playlists their positions
+            let rank = text(APPLE_ROW_NUMBER).and_then(|rank| rank.trim().parse().ok());
+            let track = parse_apple_row(
+                &text(APPLE_ROW_NAME)?,
+                text(APPLE_ROW_ARTIST).as_deref(),
+                header_artist.as_deref(),
+                album.as_deref(),
+                rank.filter(|_| is_album),
+            )?;
+            Some(Track {
+                position: rank.filter(|_| !is_album),
+                year,
+                cover_url: options.cover_art.then(|| super::cover_url(&el)).flatten(),
+                source_url: super::row_link(&el, APPLE_ROW_LINK, "https://music.apple.com"),
+                ..track
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if tracks.is_empty() {
+        return Err(PlaylistError::Empty.into());
+    }
+    if let Some(max_tracks) = options.max_tracks.filter(|&max| tracks.len() > max) {
+        log::warn!("Reached the cap of {max_tracks} tracks, dropping the rest");
+        tracks.truncate(max_tracks);
+    }
+    tracks
+        .iter()
+        .for_each(|track| super::log_scraped(track, options));
+    let reporter = Reporter::new(options.progress, "scraped", None);
+    reporter.advance(tracks.len() as u64);
+    reporter.finish();
+
+    if let Err(e) = tab.close(true) {
+        log::error!("Failed to close tab with {e:?}")
+    }
+    log::info!("Got tracks: {tracks:?}");
+    Ok(Playlist {
+        source_platform: Platform::Apple,
+        tracks,
+        reported_total,
+    })
+}
+
+/// Builds a track from a row, album rows without an artist take the header's
+pub fn parse_apple_row(
+    name: &str,
+    row_artist: Option<&str>,
+    header_artist: Option<&str>,
+    album: Option<&str>,
+    track_number: Option<u32>,
+) -> Option<Track> {
+    let name = name.trim();
+    let artist = row_artist
+        .map(str::trim)
+        .filter(|artist| !artist.is_empty())
+        .or(header_artist)?;
+    if name.is_empty() {
+        return None;
+    }
+    Some(Track {
+        name: name.to_string(),
+        artist: artist.to_string(),
+        album: album.map(str::to_string),
+        track_number,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,191 @@
+use anyhow::anyhow;
+use headless_chrome::{Browser, Tab};
+use url::Url;
+
+use crate::{
+    browser::{open_page, open_warm_tab, screenshot_on_error},
+    converter::Options,
+    resolve, youtube_link, Platform, Playlist, Track,
+};
+
+/// Channel name suffixes of YouTube's auto-generated and label channels, e.g. `Adele - Topic`
+const YT_CHANNEL_SUFFIXES: &[&str] = &[" - Topic", "VEVO"];
+
+/// Reads a single song page, `open.spotify.com/track/...` or `music.apple.com/.../song/...`,
+/// into a one-track playlist
+///
+/// The album, length and year come from the same page reader the enrichment uses.
+pub(crate) fn fetch_song(
+    browser: &Browser,
+    url: &Url,
+    platform: Platform,
+    options: &Options,
+) -> anyhow::Result<Playlist> {
+    let tab = open_warm_tab(browser, platform, options)?;
+    let lookup = resolve::lookup(&platform)?;
+    let metadata = screenshot_on_error(&tab, options, lookup(&tab, url.as_str(), options))?;
+    let song = match platform {
+        Platform::Apple => resolve::apple_recording(&tab)
+            .and_then(|recording| recording.ok_or_else(|| anyhow!("Missing the song data")))
+            .and_then(|recording| parse_apple_recording(&recording)),
+        _ => spotify_song(&tab).map(|(name, artist)| Track {
+            name,
+            artist,
+            ..Default::default()
+        }),
+    };
+    let song = screenshot_on_error(&tab, options, song)
+        .map_err(|e| e.context(format!("No song on {url}")))?;
+
+    let mut track = Track {
+        source_url: Some(url.to_string()),
+        ..song
+    };
+    metadata.apply(&mut track);
+    super::log_scraped(&track, options);
+
+    if let Err(e) = tab.close(true) {
+        log::error!("Failed to close tab with {e:?}")
+    }
+    Ok(Playlist {
+        source_platform: platform,
+        tracks: vec![track],
+        reported_total: None,
+    })
+}
+
+/// Reads a single YouTube video, `youtube.com/watch?v=...` or its `music.youtube.com` form,
+/// into a one-track playlist from its title and channel
+pub(crate) fn fetch_video(
+    browser: &Browser,
+    url: &Url,
+    id: &str,
+    options: &Options,
+) -> anyhow::Result<Playlist> {
+    // The regular watch page holds its title and channel in the served HTML
+    let tab = open_page(
+        browser,
+        &youtube_link(id, false),
+        Platform::Youtube,
+        options,
+    )?;
+    let meta = |selector: &str| {
+        tab.find_element(selector)
+            .and_then(|el| el.get_attribute_value("content"))
+            .ok()
+            .flatten()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let video = meta(r#"meta[name="title"]"#)
+        .ok_or_else(|| anyhow!("Missing the video title"))
+        .and_then(|title| {
+            let channel = meta(r#"span[itemprop="author"] link[itemprop="name"]"#)
+                .ok_or_else(|| anyhow!("Missing the channel of {title}"))?;
+            Ok((title, channel))
+        });
+    let (title, channel) = screenshot_on_error(&tab, options, video)
+        .map_err(|e| e.context(format!("No video on {url}")))?;
+    let (name, artist) = video_song(&title, &channel);
+
+    let track = Track {
+        name,
+        artist,
+        source_url: Some(url.to_string()),
+        ..Default::default()
+    };
+    super::log_scraped(&track, options);
+
+    if let Err(e) = tab.close(true) {
+        log::error!("Failed to close tab with {e:?}")
+    }
+    Ok(Playlist {
+        source_platform: Platform::Youtube,
+        tracks: vec![track],
+        reported_total: None,
+    })
+}
+
+/// Song name and artist of a video, from an `Artist - Title` title or else the channel
+fn video_song(title: &str, channel: &str) -> (String, String) {
+    if let Some((artist, name)) = title.split_once(" - ") {
+        return (name.trim().to_string(), artist.trim().to_string());
+    }
+    let artist = YT_CHANNEL_SUFFIXES
+        .iter()
+        .find_map(|suffix| channel.strip_suffix(suffix))
+        .unwrap_or(channel);
+    (title.to_string(), artist.trim().to_string())
+}
+
+/// Title and artist of a Spotify track page from its Open Graph and `music:` meta tags
+///
+/// The description reads `Artist · Album · Song · 2011`, its first part is the fallback when the
+/// musician tag is missing.
+fn spotify_song(tab: &Tab) -> anyhow::Result<(String, String)> {
+    let meta = |attribute: &str, name: &str| {
+        tab.find_element(&format!(r#"meta[{attribute}="{name}"]"#))
+            .and_then(|el| el.get_attribute_value("content"))
+            .ok()
+            .flatten()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let name = meta("property", "og:title").ok_or_else(|| anyhow!("Missing the song title"))?;
+    let artist = meta("name", "music:musician_description")
+        .or_else(|| {
+            let description = meta("property", "og:description")?;
+            Some(description.split(" · ").next()?.trim().to_string())
+        })
+        .ok_or_else(|| anyhow!("Missing the artist of {name}"))?;
+    Ok((name, artist))
+}
+
+/// Title and artists of an Apple Music song page's `MusicRecording` JSON-LD, collaborators
+/// joined like the scraped rows and also kept apart
+pub fn parse_apple_recording(recording: &serde_json::Value) -> anyhow::Result<Track> {
+    let name = recording["name"]
+        .as_str()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| anyhow!("Missing the song title"))?;
+    // A lone artist is an object, collaborations a list of them
+    let artists = match &recording["byArtist"] {
+        serde_json::Value::Array(artists) => artists.iter().collect(),
+        artist => vec![artist],
+    };
+    let artists = artists
+        .iter()
+        .filter_map(|artist| artist["name"].as_str())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if artists.is_empty() {
+        return Err(anyhow!("Missing the artist of {name}"));
+    }
+    Ok(Track {
+        name: name.to_string(),
+        artist: artists.join(", "),
+        artists,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn video_song_splits_the_title_or_falls_back_to_the_channel() {
+        assert_eq!(
+            video_song("Adele - Hello (Official Music Video)", "AdeleVEVO"),
+            (
+                "Hello (Official Music Video)".to_string(),
+                "Adele".to_string()
+            )
+        );
+        assert_eq!(
+            video_song("Hello", "Adele - Topic"),
+            ("Hello".to_string(), "Adele".to_string())
+        );
+    }
+}
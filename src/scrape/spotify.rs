@@ -0,0 +1,314 @@
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    fs,
+    hash::{BuildHasher, Hasher},
+    thread,
+    time::Duration,
+};
+
+use headless_chrome::{Browser, Element};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    browser::{open_page, screenshot_failure},
+    converter::Options,
+    progress::Reporter,
+    PartialScrape, Platform, Playlist, PlaylistError, Track, TrackKey,
+};
+
+/// Artist line of a playlist row by layout, the first one holding text wins
+///
+/// Every artist link is read on its own so collaborators are all kept, the `div` some layouts
+/// wrap the line in is the fallback.
+const SPOTIFY_ROW_ARTISTS: &[&str] = &[r#"a[href*="/artist/"]"#, "span>a", "span>div"];
+
+/// Link to the song page, whose `div` holds the title
+const SPOTIFY_ROW_LINK: &str = r#"a[data-testid="internal-track-link"]"#;
+
+/// Header line counting the songs, like `50 songs, 3 hr 2 min`
+const SPOTIFY_HEADER_TOTAL: &str =
+    r#"[data-testid="playlist-page"] [data-testid="entityHeader"], .main-entityHeader-metaData"#;
+
+/// Index column of a playlist row
+const SPOTIFY_ROW_INDEX: &str = r#"div[aria-colindex="1"]"#;
+
+pub(crate) fn fetch_spotify_playlist(
+    browser: &Browser,
+    playlist_url: impl AsRef<str>,
+    options: &Options,
+) -> anyhow::Result<Playlist> {
+    log::info!("Starting scraping spotify playlist");
+    let tab = open_page(browser, playlist_url.as_ref(), Platform::Spotify, options)?;
+    let reported_total = super::reported_total(&tab, SPOTIFY_HEADER_TOTAL);
+
+    let checkpoint_url = checkpoint_url(playlist_url.as_ref());
+    let mut tracks = load_checkpoint(options, &checkpoint_url);
+    let mut seen = tracks
+        .iter()
+        .map(|track| row_identity(track, options))
+        .collect::<HashSet<_>>();
+    // Rows already parsed on this page, checkpointed tracks are met again while scrolling
+    let mut rows_read = 0;
+    let mut stalled_iterations = 0;
+    let mut failed_iterations = 0;
+    let reporter = Reporter::new(options.progress, "scraped", None);
+    reporter.advance(tracks.len() as u64);
+
+    let mut capped = false;
+    for iteration in 0.. {
+        if options.max_scroll_iterations == Some(iteration) {
+            log::warn!(
+                "Stopped scrolling the Spotify playlist after {iteration} iterations with {} tracks, it may be incomplete",
+                tracks.len()
+            );
+            capped = true;
+            break;
+        }
+        if iteration > 0 {
+            pause_scroll(options.scroll_delay);
+        }
+        let buf = tab
+            .wait_for_elements(r#"div[data-testid="playlist-tracklist"]>div>div>div:has(a[data-testid="internal-track-link"] > div)"#)
+            .map(|els| els
+            .into_iter()
+            .skip(rows_read)
+            .filter_map(|el| {
+                if let Err(e) = el.scroll_into_view() {
+                    log::warn!("Failed to scroll to element: {e:?}");
+                }
+                let name = el.find_element("a>div").and_then(|el| el.get_inner_text());
+                let artists = row_artist(&el).unwrap_or_default();
+                let index = el
+                    .find_element(SPOTIFY_ROW_INDEX)
+                    .and_then(|el| el.get_inner_text())
+                    .ok();
+                let cover_url = options.cover_art.then(|| super::cover_url(&el)).flatten();
+                let source_url = super::row_link(&el, SPOTIFY_ROW_LINK, "https://open.spotify.com");
+
+                log::info!("Name: {name:?}, artists: {artists:?}");
+                match name.ok().and_then(|name| parse_spotify_row(&name, artists, index.as_deref())) {
+                    Some(track) => Some(Track {
+                        cover_url,
+                        source_url,
+                        ..track
+                    }),
+                    None => {
+                        log::warn!("Failed to parse track");
+                        None
+                    },
+                }
+            })
+            .collect::<Vec<_>>());
+
+        let mut tracks_added = 0;
+        let rows_before = rows_read;
+
+        match buf {
+            Err(e) => {
+                log::error!("Failed to collect buffer of tracks: {e:?}");
+                // A page without a single row may be telling why
+                let e = if tracks.is_empty() {
+                    super::explain_missing_rows(&tab, Platform::Spotify, e)
+                } else {
+                    e
+                };
+                if e.downcast_ref::<PlaylistError>().is_some() {
+                    screenshot_failure(&tab, options);
+                    return Err(e);
+                }
+                failed_iterations += 1;
+                if failed_iterations >= options.scroll_stall_threshold {
+                    screenshot_failure(&tab, options);
+                    return Err(e.context(PartialScrape(Playlist {
+                        source_platform: Platform::Spotify,
+                        tracks,
+                        reported_total,
+                    })));
+                }
+                continue;
+            }
+            Ok(buf) => {
+                failed_iterations = 0;
+                rows_read += buf.len();
+                for track in buf {
+                    if !seen.insert(row_identity(&track, options)) {
+                        continue;
+                    }
+                    if options.stream_tracks {
+                        eprintln!("{} — {}", track.name, track.artist);
+                    }
+                    super::log_scraped(&track, options);
+                    tracks.push(track);
+                    tracks_added += 1;
+                }
+            }
+        }
+
+        log::info!("Added {tracks_added} new tracks");
+        reporter.advance(tracks_added);
+        if tracks_added > 0 {
+            save_checkpoint(options, &checkpoint_url, &tracks);
+        }
+
+        if let Some(max_tracks) = options.max_tracks.filter(|&max| tracks.len() >= max) {
+            log::warn!("Reached the cap of {max_tracks} tracks, stopping the scrape");
+            tracks.truncate(max_tracks);
+            capped = true;
+            break;
+        }
+
+        // Virtualized rows can render nothing new for a moment mid-playlist
+        if rows_read == rows_before {
+            stalled_iterations += 1;
+            if stalled_iterations >= options.scroll_stall_threshold {
+                break;
+            }
+        } else {
+            stalled_iterations = 0;
+        }
+    }
+
+    reporter.finish();
+    log::info!("Finished with {} tracks", tracks.len());
+    // A capped scrape may resume from its checkpoint with a higher cap
+    if let Some(path) = options.scroll_checkpoint.as_ref().filter(|_| !capped) {
+        if let Err(e) = fs::remove_file(path) {
+            log::warn!(
+                "Failed to remove the scroll checkpoint {}: {e}",
+                path.display()
+            );
+        }
+    }
+    Ok(Playlist {
+        source_platform: Platform::Spotify,
+        tracks,
+        reported_total,
+    })
+}
+
+/// What makes two scraped rows the same, re-read rows share their index
+///
+/// Without `keep_duplicates` a song counts once wherever it's listed.
+fn row_identity(track: &Track, options: &Options) -> (Option<u32>, TrackKey) {
+    let position = track.position.filter(|_| options.keep_duplicates);
+    (position, track.key())
+}
+
+/// What a scroll checkpoint file holds, the playlist it was saved for and its tracks so far
+#[derive(Deserialize, Serialize)]
+struct Checkpoint {
+    playlist_url: String,
+    tracks: Vec<Track>,
+}
+
+/// The playlist URL a checkpoint is kept for, without the `?si=` share query that changes
+/// between copies of the same link
+fn checkpoint_url(playlist_url: &str) -> String {
+    match Url::parse(playlist_url) {
+        Ok(mut url) => {
+            url.set_query(None);
+            url.set_fragment(None);
+            url.into()
+        }
+        Err(_) => playlist_url.to_string(),
+    }
+}
+
+/// Tracks saved by an earlier, unfinished run of the same playlist with the same scroll checkpoint
+fn load_checkpoint(options: &Options, playlist_url: &str) -> Vec<Track> {
+    let Some(path) = options
+        .scroll_checkpoint
+        .as_ref()
+        .filter(|path| path.exists())
+    else {
+        return vec![];
+    };
+    let checkpoint = fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(serde_json::from_str::<Checkpoint>(&content)?));
+    match checkpoint {
+        Ok(checkpoint) if checkpoint.playlist_url != playlist_url => {
+            log::warn!(
+                "Ignoring the scroll checkpoint {}, it was saved for {}",
+                path.display(),
+                checkpoint.playlist_url
+            );
+            vec![]
+        }
+        Ok(checkpoint) => {
+            log::info!(
+                "Resuming from {} tracks in {}",
+                checkpoint.tracks.len(),
+                path.display()
+            );
+            checkpoint.tracks
+        }
+        Err(e) => {
+            log::warn!("Ignoring the scroll checkpoint {}: {e}", path.display());
+            vec![]
+        }
+    }
+}
+
+fn save_checkpoint(options: &Options, playlist_url: &str, tracks: &[Track]) {
+    let Some(path) = &options.scroll_checkpoint else {
+        return;
+    };
+    let checkpoint = Checkpoint {
+        playlist_url: playlist_url.to_string(),
+        tracks: tracks.to_vec(),
+    };
+    let saved = serde_json::to_string(&checkpoint)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(fs::write(path, content)?));
+    if let Err(e) = saved {
+        log::warn!(
+            "Failed to save the scroll checkpoint {}: {e}",
+            path.display()
+        );
+    }
+}
+
+/// Sleeps `delay` plus up to half of it, so scrolls don't come at a machine-regular pace
+fn pause_scroll(delay: Duration) {
+    if delay.is_zero() {
+        return;
+    }
+    // A freshly keyed hasher is random enough for jitter without another dependency
+    let random = RandomState::new().build_hasher().finish();
+    let jitter = delay.mul_f64((random % 1000) as f64 / 2000.0);
+    log::debug!("Waiting {:?} before the next scroll", delay + jitter);
+    thread::sleep(delay + jitter);
+}
+
+/// Builds a track from a row's title, artist links and index, the artists joined like Spotify's
+/// own artist line
+pub fn parse_spotify_row(name: &str, artists: Vec<String>, index: Option<&str>) -> Option<Track> {
+    let name = name.trim();
+    if name.is_empty() || artists.is_empty() {
+        return None;
+    }
+    Some(Track {
+        name: name.to_string(),
+        artist: artists.join(", "),
+        artists,
+        position: index.and_then(|index| index.trim().parse().ok()),
+        ..Default::default()
+    })
+}
+
+/// Artists of a row, one per link when they are separate links
+fn row_artist(row: &Element) -> Option<Vec<String>> {
+    SPOTIFY_ROW_ARTISTS.iter().find_map(|selector| {
+        let artists = row
+            .find_elements(selector)
+            .ok()?
+            .into_iter()
+            .filter_map(|el| el.get_inner_text().ok())
+            .map(|artist| artist.trim().to_string())
+            .filter(|artist| !artist.is_empty())
+            .collect::<Vec<_>>();
+        (!artists.is_empty()).then_some(artists)
+    })
+}
@@ -0,0 +1,134 @@
+use headless_chrome::Browser;
+
+use crate::{
+    browser::{open_page, screenshot_on_error},
+    converter::Options,
+    normalize::is_duration,
+    progress::Reporter,
+    Platform, Playlist, PlaylistError, Track,
+};
+
+/// Podcast episode rows link to their show, whose browse ids start with `MPSP`
+const YT_PODCAST_LINK: &str = r#"a[href*="browse/MPSP"]"#;
+
+/// Type and year line under an album's title, like `Album • 2011`
+const YT_HEADER_SUBTITLE: &str = "ytmusic-responsive-header-renderer .subtitle";
+
+/// Count line under the title, like `50 songs • 3+ hours`
+const YT_HEADER_TOTAL: &str = "ytmusic-responsive-header-renderer .second-subtitle";
+
+/// Link playing the song, on the title
+const YT_ROW_LINK: &str = r#"a[href*="watch?v="]"#;
+
+/// Numbering column of album rows, absent on playlists
+const YT_TRACK_NUMBER: &str = ".index";
+
+pub(crate) fn fetch_yt_playlist(
+    browser: &Browser,
+    yt_playlist_url: impl AsRef<str>,
+    options: &Options,
+) -> anyhow::Result<Playlist> {
+    let tab = open_page(
+        browser,
+        yt_playlist_url.as_ref(),
+        Platform::Youtube,
+        options,
+    )?;
+    let rows = tab.wait_for_elements("ytmusic-responsive-list-item-renderer");
+    // Playlist headers show the year they were made, only albums carry a release year
+    let year = is_yt_album(yt_playlist_url.as_ref())
+        .then(|| {
+            tab.find_element(YT_HEADER_SUBTITLE)
+                .and_then(|el| el.get_inner_text())
+                .ok()
+        })
+        .flatten()
+        .and_then(|text| super::header_year(&text));
+    let (episodes, rows): (Vec<_>, Vec<_>) = screenshot_on_error(&tab, options, rows)
+        .map_err(|e| super::explain_missing_rows(&tab, Platform::Youtube, e))?
+        .into_iter()
+        .partition(|el| el.find_element(YT_PODCAST_LINK).is_ok());
+    if !episodes.is_empty() {
+        log::info!("Skipped {} podcast episodes", episodes.len());
+    }
+    // The header counts the skipped episodes too
+    let reported_total = super::reported_total(&tab, YT_HEADER_TOTAL)
+        .map(|total| total.saturating_sub(episodes.len()));
+    let mut tracks = rows
+        .into_iter()
+        .filter_map(|el| {
+            let strings = el.find_elements("yt-formatted-string").ok()?;
+            let track_number = el
+                .find_element(YT_TRACK_NUMBER)
+                .and_then(|el| el.get_inner_text())
+                .ok()
+                .and_then(|index| index.trim().parse().ok());
+            let track_info = strings
+                .into_iter()
+                .filter_map(|el| el.get_inner_text().ok())
+                .collect::<Vec<_>>();
+            let cover_url = options.cover_art.then(|| super::cover_url(&el)).flatten();
+            let source_url = super::row_link(&el, YT_ROW_LINK, "https://music.youtube.com");
+            Some((track_info, track_number, cover_url, source_url))
+        })
+        .filter_map(|(track_info, track_number, cover_url, source_url)| {
+            let Some(track) = parse_yt_row(&track_info, track_number) else {
+                log::warn!("Skipped a row without name and artist: {track_info:?}");
+                return None;
+            };
+            Some(Track {
+                cover_url,
+                source_url,
+                year,
+                ..track
+            })
+        })
+        .collect::<Vec<_>>();
+    if tracks.is_empty() && episodes.is_empty() {
+        return Err(PlaylistError::Empty.into());
+    }
+    if let Some(max_tracks) = options.max_tracks.filter(|&max| tracks.len() > max) {
+        log::warn!("Reached the cap of {max_tracks} tracks, dropping the rest");
+        tracks.truncate(max_tracks);
+    }
+    tracks
+        .iter()
+        .for_each(|track| super::log_scraped(track, options));
+    let reporter = Reporter::new(options.progress, "scraped", None);
+    reporter.advance(tracks.len() as u64);
+    reporter.finish();
+    log::info!("Got tracks: {tracks:?}");
+    Ok(Playlist {
+        source_platform: Platform::Youtube,
+        tracks,
+        reported_total,
+    })
+}
+
+/// Builds a track from the texts of a row's `yt-formatted-string` elements
+///
+/// Only the name and artist are required, rows whose album failed to render keep their track.
+pub fn parse_yt_row(track_info: &[String], track_number: Option<u32>) -> Option<Track> {
+    let [name, artist, rest @ ..] = track_info else {
+        return None;
+    };
+    if name.is_empty() || artist.is_empty() {
+        return None;
+    }
+    let album = rest
+        .first()
+        .filter(|album| !album.is_empty() && !is_duration(album))
+        .cloned();
+    Some(Track {
+        name: name.to_string(),
+        artist: artist.to_string(),
+        album,
+        track_number,
+        ..Default::default()
+    })
+}
+
+/// Whether the link opens an album, either its browse page or its `OLAK5uy_` playlist
+fn is_yt_album(url: &str) -> bool {
+    url.contains("/browse/MPRE") || url.contains("list=OLAK5uy_")
+}
@@ -0,0 +1,147 @@
+use std::{fmt, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Platform;
+
+/// CSS selectors of the target resolvers, overridable from a JSON file when a site changes
+///
+/// Every field falls back to its built-in default, so a file only lists the broken ones:
+///
+/// ```json
+/// { "apple": { "song_artist": ".new-subtitle-class" } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Selectors {
+    pub apple: AppleSelectors,
+    pub spotify: SpotifySelectors,
+}
+
+/// Selectors of the Apple Music search page
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AppleSelectors {
+    /// First section listing songs, `div[aria-label]:has(li a[href*="/song/"], li a[href*="?i="])`
+    ///
+    /// Matched by structure since the `aria-label` is localized in non-English storefronts.
+    pub songs_section: String,
+    /// Link to a song, either its own page or its position on an album,
+    /// `a[href*="/song/"], a[href*="?i="]`
+    pub song_link: String,
+    /// Artist line of a song, `.track-lockup__subtitle`
+    pub song_artist: String,
+    /// Explicit badge of a song, `[data-testid="explicit-badge"], .explicit-indicator`
+    pub explicit_badge: String,
+    /// Placeholder rendered instead of any result sections,
+    /// `[data-testid="no-results"], .search__no-results`
+    pub no_results: String,
+    /// "Stay on web" button of the "Open in the Music app?" interstitial,
+    /// `[data-testid="stay-on-web"], .web-to-native__dismiss`
+    pub open_in_app_dismiss: String,
+}
+
+impl Default for AppleSelectors {
+    fn default() -> Self {
+        Self {
+            songs_section: r#"div[aria-label]:has(li a[href*="/song/"], li a[href*="?i="])"#
+                .to_string(),
+            song_link: r#"a[href*="/song/"], a[href*="?i="]"#.to_string(),
+            song_artist: ".track-lockup__subtitle".to_string(),
+            explicit_badge: r#"[data-testid="explicit-badge"], .explicit-indicator"#.to_string(),
+            no_results: r#"[data-testid="no-results"], .search__no-results"#.to_string(),
+            open_in_app_dismiss: r#"[data-testid="stay-on-web"], .web-to-native__dismiss"#
+                .to_string(),
+        }
+    }
+}
+
+/// Selectors of the Spotify search page
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpotifySelectors {
+    /// Song row, `div[data-testid="tracklist-row"]`
+    pub search_row: String,
+    /// Link to the song page whose first `div` holds the title,
+    /// `a[data-testid="internal-track-link"]`
+    pub track_link: String,
+    /// Artist links of a row, `a[href*="/artist/"]`
+    pub artist_link: String,
+    /// Explicit badge of a row, `span[aria-label="Explicit"]`
+    pub explicit_badge: String,
+    /// Album column of a row, `a[href*="/album/"]`
+    pub album_link: String,
+}
+
+impl Default for SpotifySelectors {
+    fn default() -> Self {
+        Self {
+            search_row: r#"div[data-testid="tracklist-row"]"#.to_string(),
+            track_link: r#"a[data-testid="internal-track-link"]"#.to_string(),
+            artist_link: r#"a[href*="/artist/"]"#.to_string(),
+            explicit_badge: r#"span[aria-label="Explicit"]"#.to_string(),
+            album_link: r#"a[href*="/album/"]"#.to_string(),
+        }
+    }
+}
+
+impl Selectors {
+    /// Reads overrides from a JSON file, missing fields keep their defaults
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+/// Page probed for a platform's layout, tagged with when its built-in selectors last matched
+pub(crate) struct LayoutProbe {
+    pub platform: Platform,
+    /// Month the scrapers and resolvers of the platform were last checked against the site
+    pub version: &'static str,
+    /// Search page rendering results for any query
+    pub url: &'static str,
+    /// Element the scrapers rely on, missing once the layout changed
+    pub sentinel: &'static str,
+}
+
+/// Probes of the platforms with built-in selectors
+pub(crate) const LAYOUT_PROBES: &[LayoutProbe] = &[
+    LayoutProbe {
+        platform: Platform::Spotify,
+        version: "2026-10",
+        url: "https://open.spotify.com/search/love/tracks",
+        sentinel: r#"div[data-testid="tracklist-row"]"#,
+    },
+    LayoutProbe {
+        platform: Platform::Apple,
+        version: "2026-10",
+        url: "https://music.apple.com/us/search?term=love",
+        sentinel: r#"a[href*="/song/"], a[href*="?i="]"#,
+    },
+    LayoutProbe {
+        platform: Platform::Youtube,
+        version: "2026-10",
+        url: "https://music.youtube.com/search?q=love",
+        sentinel: "ytmusic-responsive-list-item-renderer",
+    },
+];
+
+/// Page of a platform no longer shows the elements its built-in selectors expect
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutChanged {
+    pub platform: Platform,
+    /// Version tag of the selectors that missed
+    pub version: &'static str,
+}
+
+impl fmt::Display for LayoutChanged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The {:?} layout may have changed since these selectors were written ({})",
+            self.platform, self.version
+        )
+    }
+}
+
+impl std::error::Error for LayoutChanged {}
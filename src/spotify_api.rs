@@ -0,0 +1,252 @@
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{ApiClient, Candidate, Platform, Track, TrackResolver};
+
+const API_BASE: &str = "https://api.spotify.com/v1";
+
+/// Tracks the API accepts in a single add request
+const ADD_TRACKS_BATCH: usize = 100;
+
+/// Search results weighed per track
+const SEARCH_LIMIT: usize = 10;
+
+/// Spotify Web API calls made on behalf of a user
+///
+/// The access token needs the `playlist-modify-private` scope.
+pub struct SpotifyApi<'a> {
+    http: &'a ApiClient,
+    token: String,
+}
+
+/// Playlist created in the user's account
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreatedPlaylist {
+    pub id: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct PlaylistTracksPage {
+    items: Vec<PlaylistItem>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PlaylistItem {
+    track: Option<TrackObject>,
+}
+
+#[derive(Deserialize)]
+struct TrackObject {
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    tracks: SearchTracks,
+}
+
+#[derive(Deserialize)]
+struct SearchTracks {
+    items: Vec<SearchTrack>,
+}
+
+#[derive(Deserialize)]
+struct SearchTrack {
+    name: String,
+    artists: Vec<Named>,
+    album: SearchAlbum,
+    external_urls: ExternalUrls,
+    track_number: Option<u32>,
+    #[serde(default)]
+    explicit: bool,
+    duration_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct SearchAlbum {
+    name: Option<String>,
+    release_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct User {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct PlaylistResponse {
+    id: String,
+    external_urls: ExternalUrls,
+}
+
+#[derive(Deserialize)]
+struct ExternalUrls {
+    spotify: String,
+}
+
+impl<'a> SpotifyApi<'a> {
+    pub fn new(http: &'a ApiClient, token: impl Into<String>) -> Self {
+        Self {
+            http,
+            token: token.into(),
+        }
+    }
+
+    /// Creates a private playlist named `name`
+    pub fn create_playlist(&self, name: &str) -> anyhow::Result<CreatedPlaylist> {
+        let user: User = self
+            .send("GET", &format!("{API_BASE}/me"), None)?
+            .into_json()
+            .context("Unexpected user response")?;
+
+        let body = json!({ "name": name, "public": false });
+        let playlist: PlaylistResponse = self
+            .send(
+                "POST",
+                &format!("{API_BASE}/users/{}/playlists", user.id),
+                Some(&body),
+            )?
+            .into_json()
+            .context("Unexpected playlist response")?;
+
+        Ok(CreatedPlaylist {
+            id: playlist.id,
+            url: playlist.external_urls.spotify,
+        })
+    }
+
+    /// URIs of all tracks already in the playlist
+    pub fn playlist_track_uris(&self, playlist_id: &str) -> anyhow::Result<HashSet<String>> {
+        let mut uris = HashSet::new();
+        let mut next = Some(format!(
+            "{API_BASE}/playlists/{playlist_id}/tracks?fields=items(track(uri)),next&limit=100"
+        ));
+        while let Some(url) = next {
+            let page: PlaylistTracksPage = self
+                .send("GET", &url, None)?
+                .into_json()
+                .context("Unexpected playlist tracks response")?;
+            uris.extend(
+                page.items
+                    .into_iter()
+                    .filter_map(|item| item.track)
+                    .map(|track| track.uri),
+            );
+            next = page.next;
+        }
+        Ok(uris)
+    }
+
+    /// Appends tracks given as `spotify:track:ID` URIs to the playlist
+    pub fn add_tracks(&self, playlist_id: &str, uris: &[String]) -> anyhow::Result<()> {
+        for batch in uris.chunks(ADD_TRACKS_BATCH) {
+            let body = json!({ "uris": batch });
+            self.send(
+                "POST",
+                &format!("{API_BASE}/playlists/{playlist_id}/tracks"),
+                Some(&body),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Songs the catalog search returns for the track's title and artist
+    pub fn search_tracks(&self, track: &Track) -> anyhow::Result<Vec<Candidate>> {
+        let query = match track.artist.as_str() {
+            "" => format!("track:{}", track.name),
+            artist => format!("track:{} artist:{artist}", track.name),
+        };
+        let response: SearchResponse = self
+            .send(
+                "GET",
+                &format!(
+                    "{API_BASE}/search?type=track&limit={SEARCH_LIMIT}&q={}",
+                    urlencoding::encode(&query)
+                ),
+                None,
+            )?
+            .into_json()
+            .context("Unexpected search response")?;
+
+        let candidates = response
+            .tracks
+            .items
+            .into_iter()
+            .map(|item| Candidate {
+                title: item.name,
+                artist: item
+                    .artists
+                    .into_iter()
+                    .map(|artist| artist.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                link: item.external_urls.spotify,
+                track_number: item.track_number,
+                explicit: item.explicit,
+                year: item
+                    .album
+                    .release_date
+                    .and_then(|date| date.get(..4)?.parse().ok()),
+                album: item.album.name,
+                duration_secs: item.duration_ms.map(|ms| (ms / 1000) as u32),
+            })
+            .collect();
+        Ok(candidates)
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<&serde_json::Value>,
+    ) -> anyhow::Result<ureq::Response> {
+        let request = self
+            .http
+            .request(method, url)
+            .set("Authorization", &format!("Bearer {}", self.token));
+        self.http
+            .send(request, body)
+            .with_context(|| format!("Spotify API request to {url} failed"))
+    }
+}
+
+/// Finds Spotify songs through the Web API search, no browser needed
+///
+/// Shares the converter's [`ApiClient`] and with it the rate limit, see [`Converter::http`](crate::Converter::http).
+pub struct SpotifyApiResolver {
+    http: Arc<ApiClient>,
+    token: String,
+}
+
+impl SpotifyApiResolver {
+    pub fn new(http: Arc<ApiClient>, token: impl Into<String>) -> Self {
+        Self {
+            http,
+            token: token.into(),
+        }
+    }
+}
+
+impl TrackResolver for SpotifyApiResolver {
+    fn name(&self) -> &str {
+        "spotify-api"
+    }
+
+    fn supports(&self, target: &Platform) -> bool {
+        *target == Platform::Spotify
+    }
+
+    fn search(&self, track: &Track, _target: &Platform) -> anyhow::Result<Vec<Candidate>> {
+        SpotifyApi::new(&self.http, self.token.as_str()).search_tracks(track)
+    }
+}
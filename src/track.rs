@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::normalize::{normalize_artist, normalize_title, split_artists};
+
+/// Single song scraped from a playlist
+///
+/// `PartialEq` compares the raw scraped fields, use [`Track::key`] to find the same song
+/// written differently, e.g. when deduplicating or caching.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct Track {
+    pub name: String,
+    pub artist: String,
+    /// Each artist on its own, when the source links them separately
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    /// Position on the album, only album pages number their tracks
+    #[serde(default)]
+    pub track_number: Option<u32>,
+    #[serde(default)]
+    pub disc_number: Option<u32>,
+    /// Index shown next to the row, the playlist's own order even when the page sorts otherwise
+    #[serde(default)]
+    pub position: Option<u32>,
+    /// Length of the song, filled by enrichment from the target platform
+    #[serde(default)]
+    pub duration_secs: Option<u32>,
+    /// Release year, read from album headers or filled by enrichment from the target platform
+    #[serde(default)]
+    pub year: Option<u16>,
+    /// Link to the song on the source platform, when the row links to it
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// Album art shown on the row, only kept when scraping with cover art enabled
+    #[serde(default)]
+    pub cover_url: Option<String>,
+    /// International Standard Recording Code, the same on every platform, read from CSV
+    /// exports that list it
+    #[serde(default)]
+    pub isrc: Option<String>,
+}
+
+impl Track {
+    pub fn key(&self) -> TrackKey {
+        TrackKey {
+            name: normalize_title(&self.name),
+            artist: normalize_artist(&self.artist),
+        }
+    }
+
+    /// Artists of the track, the scraped list when there is one or else the artist line split
+    /// on its usual separators
+    ///
+    /// ```
+    /// use unilist::Track;
+    ///
+    /// let track = Track {
+    ///     artist: "Simon & Garfunkel".to_string(),
+    ///     artists: vec!["Simon & Garfunkel".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(track.artist_names(), ["Simon & Garfunkel"]);
+    /// ```
+    pub fn artist_names(&self) -> Vec<String> {
+        if self.artists.is_empty() {
+            split_artists(&self.artist)
+        } else {
+            self.artists.clone()
+        }
+    }
+}
+
+/// Normalized name and artist identifying a song regardless of casing and variant suffixes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TrackKey {
+    pub name: String,
+    pub artist: String,
+}
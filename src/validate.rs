@@ -0,0 +1,69 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{normalize::is_duration, Track};
+
+/// Problem found in a track of a playlist file, see [`validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackIssue {
+    EmptyName,
+    EmptyArtist,
+    /// Name or artist reads like `3:45`, a scraper likely took the duration column
+    DurationAsField(&'static str),
+    /// Same [`Track::key`] as the track at this index
+    Duplicate(usize),
+}
+
+impl fmt::Display for TrackIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackIssue::EmptyName => write!(f, "empty name"),
+            TrackIssue::EmptyArtist => write!(f, "empty artist"),
+            TrackIssue::DurationAsField(field) => write!(f, "{field} looks like a duration"),
+            TrackIssue::Duplicate(first) => write!(f, "duplicate of track {}", first + 1),
+        }
+    }
+}
+
+/// Checks the tracks of a loaded playlist, returning every issue with the index of its track
+///
+/// ```
+/// use unilist::{validate, Track, TrackIssue};
+///
+/// let track = |name: &str, artist: &str| Track {
+///     name: name.to_string(),
+///     artist: artist.to_string(),
+///     ..Default::default()
+/// };
+/// let issues = validate(&[track("Song", "Band"), track("3:45", "Band"), track("song", "band")]);
+/// assert_eq!(
+///     issues,
+///     [(1, TrackIssue::DurationAsField("name")), (2, TrackIssue::Duplicate(0))]
+/// );
+/// ```
+pub fn validate(tracks: &[Track]) -> Vec<(usize, TrackIssue)> {
+    let mut issues = vec![];
+    let mut seen = HashMap::new();
+    for (idx, track) in tracks.iter().enumerate() {
+        let name = track.name.trim();
+        let artist = track.artist.trim();
+        if name.is_empty() {
+            issues.push((idx, TrackIssue::EmptyName));
+        } else if is_duration(name) {
+            issues.push((idx, TrackIssue::DurationAsField("name")));
+        }
+        if artist.is_empty() {
+            issues.push((idx, TrackIssue::EmptyArtist));
+        } else if is_duration(artist) {
+            issues.push((idx, TrackIssue::DurationAsField("artist")));
+        }
+        if name.is_empty() && artist.is_empty() {
+            continue;
+        }
+        if let Some(&first) = seen.get(&track.key()) {
+            issues.push((idx, TrackIssue::Duplicate(first)));
+        } else {
+            seen.insert(track.key(), idx);
+        }
+    }
+    issues
+}